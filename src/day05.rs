@@ -1,5 +1,8 @@
-use eyre::Result;
-use std::collections::HashSet;
+use eyre::{eyre, Result};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque},
+};
 
 #[derive(Debug)]
 struct Rules(HashSet<(i32, i32)>);
@@ -24,11 +27,50 @@ impl Rules {
     fn is_match(&self, a: i32, b: i32) -> bool {
         self.0.contains(&(a, b))
     }
+
+    /// Pages without a rule between them compare as equal
+    fn compare(&self, a: i32, b: i32) -> Ordering {
+        if self.is_match(a, b) {
+            Ordering::Less
+        } else if self.is_match(b, a) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    }
+
+    /// The comparator is only meaningful if the rules form a total order on
+    /// the pages of the update
+    fn check_total_order(&self, produce: &Produce) -> Result<()> {
+        let pages = &produce.0;
+
+        for (idx, &a) in pages.iter().enumerate() {
+            for &b in pages[idx + 1..].iter() {
+                match (self.is_match(a, b), self.is_match(b, a)) {
+                    (false, false) => return Err(eyre!("No rule between {} and {}", a, b)),
+                    (true, true) => {
+                        return Err(eyre!("Conflicting rules {}|{} and {}|{}", a, b, b, a))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        reorder(produce, self).map(|_| ())
+    }
 }
 
 #[derive(Debug)]
 struct Produce(Vec<i32>);
 
+/// Pages printed in the wrong order, `rule` is the one they break
+#[derive(Debug, PartialEq)]
+struct Violation {
+    earlier: i32,
+    later: i32,
+    rule: String,
+}
+
 impl Produce {
     pub fn parse(input: &str) -> Self {
         let pages = input
@@ -39,6 +81,7 @@ impl Produce {
         Self(pages)
     }
 
+    /// Every pair of pages follows a rule
     pub fn is_valid(&self, rules: &Rules) -> bool {
         let total = self.0.len();
 
@@ -47,6 +90,37 @@ impl Produce {
             .all(|(a, b)| rules.is_match(self.0[a], self.0[b]))
     }
 
+    /// Same as `is_valid` when the rules totally order the pages, checking
+    /// only neighbours
+    fn is_sorted(&self, rules: &Rules) -> bool {
+        self.0.is_sorted_by(|&a, &b| rules.is_match(a, b))
+    }
+
+    pub fn violations(&self, rules: &Rules) -> Vec<Violation> {
+        let pages = &self.0;
+        let mut result = vec![];
+
+        for (idx, &earlier) in pages.iter().enumerate() {
+            for &later in pages[idx + 1..].iter() {
+                if rules.is_match(later, earlier) {
+                    result.push(Violation {
+                        earlier,
+                        later,
+                        rule: format!("{}|{}", later, earlier),
+                    });
+                }
+            }
+        }
+
+        result
+    }
+
+    pub fn sorted(&self, rules: &Rules) -> Self {
+        let mut pages = self.0.clone();
+        pages.sort_by(|&a, &b| rules.compare(a, b));
+        Self(pages)
+    }
+
     fn get_middle(&self) -> i32 {
         let idx = self.0.len() / 2;
         self.0[idx]
@@ -56,6 +130,8 @@ impl Produce {
 struct Game {
     rules: Rules,
     produce: Vec<Produce>,
+    /// Whether the rules totally order the pages of each update, and why not
+    ordered: Vec<Result<()>>,
 }
 
 impl Game {
@@ -64,64 +140,330 @@ impl Game {
         let rules = parts.next().map(Rules::parse);
         let produce = parts
             .next()
-            .map(|part| part.lines().map(Produce::parse).collect());
+            .map(|part| part.lines().map(Produce::parse).collect::<Vec<_>>());
 
         match (rules, produce) {
-            (Some(rules), Some(produce)) => Self { rules, produce },
+            (Some(rules), Some(produce)) => {
+                let ordered = produce.iter().map(|p| rules.check_total_order(p)).collect();
+
+                Self {
+                    rules,
+                    produce,
+                    ordered,
+                }
+            }
             _ => panic!("incorrect input"),
         }
     }
 
+    fn is_valid(&self, idx: usize) -> bool {
+        if self.ordered[idx].is_ok() {
+            self.produce[idx].is_sorted(&self.rules)
+        } else {
+            self.produce[idx].is_valid(&self.rules)
+        }
+    }
+
     fn solve_a(&self) -> i32 {
-        self.produce
-            .iter()
-            .filter(|p| p.is_valid(&self.rules))
-            .map(|p| p.get_middle())
+        (0..self.produce.len())
+            .filter(|&idx| self.is_valid(idx))
+            .map(|idx| self.produce[idx].get_middle())
             .sum()
     }
 
-    fn solve_b(&self) -> i32 {
-        self.produce
-            .iter()
-            .filter(|p| !p.is_valid(&self.rules))
-            .map(|p| reorder(p, &self.rules))
-            .map(|p| p.get_middle())
+    fn solve_b(&self) -> Result<i32> {
+        (0..self.produce.len())
+            .filter(|&idx| !self.is_valid(idx))
+            .map(|idx| {
+                let produce = &self.produce[idx];
+                let sorted = if self.ordered[idx].is_ok() {
+                    produce.sorted(&self.rules)
+                } else {
+                    // missing rules may still follow by transitivity, only a
+                    // cycle is an error
+                    reorder(produce, &self.rules)?
+                };
+                Ok(sorted.get_middle())
+            })
             .sum()
     }
+
+    /// Violated rules with the number of times they are broken, most frequent first
+    fn violation_report(&self) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for produce in self.produce.iter() {
+            for violation in produce.violations(&self.rules) {
+                *counts.entry(violation.rule).or_default() += 1;
+            }
+        }
+
+        let mut result = counts.into_iter().collect::<Vec<_>>();
+        result.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        result
+    }
+
+    /// Updates the rules don't totally order, with the reason
+    fn inconsistent(&self) -> Vec<(usize, &eyre::Report)> {
+        self.ordered
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, result)| result.as_ref().err().map(|e| (idx, e)))
+            .collect()
+    }
 }
 
-fn reorder(input: &Produce, rules: &Rules) -> Produce {
+/// Kahn's algorithm over the rules between pages of a single update
+fn reorder(input: &Produce, rules: &Rules) -> Result<Produce> {
+    let pages = &input.0;
+    let mut incoming = pages
+        .iter()
+        .map(|&b| pages.iter().filter(|&&a| rules.is_match(a, b)).count())
+        .collect::<Vec<_>>();
+
+    let mut queue = (0..pages.len())
+        .filter(|&idx| incoming[idx] == 0)
+        .collect::<VecDeque<_>>();
     let mut items = vec![];
-    let mut rest: HashSet<_> = input.0.iter().cloned().collect();
 
-    while !rest.is_empty() {
-        let founded = find_head(&rest, rules);
-        items.push(founded);
-        rest.remove(&founded);
+    while let Some(idx) = queue.pop_front() {
+        items.push(pages[idx]);
+
+        for next in 0..pages.len() {
+            if incoming[next] > 0 && rules.is_match(pages[idx], pages[next]) {
+                incoming[next] -= 1;
+                if incoming[next] == 0 {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    if items.len() < pages.len() {
+        let rest = (0..pages.len())
+            .filter(|&idx| incoming[idx] > 0)
+            .map(|idx| pages[idx])
+            .collect::<Vec<_>>();
+
+        let cycle = find_cycle(&rest, rules)
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<_>>();
+
+        return Err(eyre!(
+            "Rules contain a cycle: {} (update {:?})",
+            cycle.join(" -> "),
+            pages
+        ));
     }
 
-    Produce(items)
+    Ok(Produce(items))
 }
 
-fn find_head(input: &HashSet<i32>, rules: &Rules) -> i32 {
-    let founded = input.iter().find(|&&a| {
-        input
+/// Every page left after Kahn's algorithm has a predecessor among the others,
+/// so walking back along the rules always comes back to a visited page
+fn find_cycle(rest: &[i32], rules: &Rules) -> Vec<i32> {
+    let mut path = vec![rest[0]];
+
+    loop {
+        let current = path[path.len() - 1];
+        let prev = *rest
             .iter()
-            .filter(|&&b| b != a)
-            .all(|&b| rules.is_match(a, b))
-    });
+            .find(|&&page| rules.is_match(page, current))
+            .expect("Every page has a predecessor");
+
+        if let Some(start) = path.iter().position(|&page| page == prev) {
+            let mut cycle = path[start..].to_vec();
+            cycle.reverse();
+            cycle.push(cycle[0]);
+            return cycle;
+        }
 
-    *founded.expect("AHAHA")
+        path.push(prev);
+    }
 }
 
 fn main() -> Result<()> {
     let data = std::fs::read_to_string("data/day05.txt")?;
     let game = Game::new(&data);
 
+    if std::env::args().any(|arg| arg == "--check") {
+        let inconsistent = game.inconsistent();
+        for (idx, error) in inconsistent.iter() {
+            println!("update {}: {}", idx + 1, error);
+        }
+        println!("{} inconsistent updates", inconsistent.len());
+
+        return Ok(());
+    }
+
+    if std::env::args().any(|arg| arg == "--violations") {
+        let invalid = (0..game.produce.len())
+            .filter(|&idx| !game.is_valid(idx))
+            .count();
+        let report = game.violation_report();
+        let total: usize = report.iter().map(|(_, count)| count).sum();
+
+        println!(
+            "{} invalid updates, {} violations of {} rules",
+            invalid,
+            total,
+            report.len()
+        );
+        for (rule, count) in report.iter().take(10) {
+            println!("{}: {}", rule, count);
+        }
+
+        return Ok(());
+    }
+
     let a = game.solve_a();
-    let b = game.solve_b();
+    let b = game.solve_b()?;
 
     println!("A: {}\nB: {}", a, b);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47";
+
+    #[test]
+    fn test_example() {
+        let game = Game::new(EXAMPLE);
+
+        assert_eq!(game.solve_a(), 143);
+        assert_eq!(game.solve_b().unwrap(), 123);
+    }
+
+    #[test]
+    fn test_reorder() {
+        let game = Game::new(EXAMPLE);
+        let produce = reorder(&Produce::parse("97,13,75,29,47"), &game.rules).unwrap();
+
+        assert_eq!(produce.0, [97, 75, 47, 29, 13]);
+    }
+
+    #[test]
+    fn test_sorted() {
+        let game = Game::new(EXAMPLE);
+
+        for produce in game.produce.iter() {
+            let sorted = produce.sorted(&game.rules);
+            assert!(sorted.is_valid(&game.rules));
+            assert_eq!(sorted.0, reorder(produce, &game.rules).unwrap().0);
+        }
+    }
+
+    #[test]
+    fn test_check_total_order() {
+        let game = Game::new(EXAMPLE);
+        assert!(game.inconsistent().is_empty());
+
+        let rules = Rules::parse("1|2\n2|3\n3|1\n2|1\n4|5");
+        let error = |input| rules.check_total_order(&Produce::parse(input)).unwrap_err();
+
+        assert_eq!(error("4,1").to_string(), "No rule between 4 and 1");
+        assert_eq!(error("1,2").to_string(), "Conflicting rules 1|2 and 2|1");
+
+        let rules = Rules::parse("1|2\n2|3\n3|1");
+        let error = rules
+            .check_total_order(&Produce::parse("1,2,3"))
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Rules contain a cycle"));
+    }
+
+    #[test]
+    fn test_partial_order() {
+        let game = Game::new("1|2\n2|3\n3|4\n4|5\n\n2,1,3,4,5");
+        let inconsistent = game.inconsistent();
+
+        assert_eq!(inconsistent.len(), 1);
+        assert_eq!(inconsistent[0].1.to_string(), "No rule between 2 and 4");
+        assert_eq!(game.solve_a(), 0);
+        assert_eq!(game.solve_b().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_cyclic_rules() {
+        let game = Game::new("1|2\n2|3\n3|1\n\n1,2,3\n1,2");
+
+        assert!(!game.is_valid(0));
+        assert!(game.is_valid(1));
+        assert_eq!(game.solve_a(), 2);
+
+        let error = game.solve_b().unwrap_err();
+        assert!(error.to_string().starts_with("Rules contain a cycle"));
+    }
+
+    #[test]
+    fn test_violations() {
+        let game = Game::new(EXAMPLE);
+        let violations = Produce::parse("61,13,29").violations(&game.rules);
+
+        assert_eq!(
+            violations,
+            [Violation {
+                earlier: 13,
+                later: 29,
+                rule: "29|13".to_string()
+            }]
+        );
+
+        let valid = game
+            .produce
+            .iter()
+            .filter(|p| p.violations(&game.rules).is_empty())
+            .count();
+        assert_eq!(valid, 3);
+    }
+
+    #[test]
+    fn test_violation_report() {
+        let game = Game::new(EXAMPLE);
+        let report = game.violation_report();
+
+        assert_eq!(report[0], ("29|13".to_string(), 2));
+        assert_eq!(report.iter().map(|(_, c)| c).sum::<usize>(), 6);
+    }
+
+    #[test]
+    fn test_reorder_cycle() {
+        let rules = Rules::parse("1|2\n2|3\n3|1\n4|1");
+        let error = reorder(&Produce::parse("4,3,2,1"), &rules).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Rules contain a cycle: 1 -> 2 -> 3 -> 1 (update [4, 3, 2, 1])"
+        );
+    }
+}
@@ -1,5 +1,5 @@
-use eyre::Result;
-use std::collections::HashSet;
+use eyre::{eyre, Result};
+use std::{collections::HashSet, fs, path::Path, time::Instant};
 
 #[derive(Debug, PartialEq)]
 enum RunResult {
@@ -47,6 +47,41 @@ impl Position {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Empty,
+    Wall,
+    Obstacle,
+    Vertical,
+    Horizontal,
+    Cross,
+    Guard(Direction),
+}
+
+impl Cell {
+    fn to_char(self) -> char {
+        match self {
+            Self::Empty => '.',
+            Self::Wall => '#',
+            Self::Obstacle => 'O',
+            Self::Vertical => '|',
+            Self::Horizontal => '-',
+            Self::Cross => '+',
+            Self::Guard(direction) => direction.to_char(),
+        }
+    }
+
+    fn to_rgb(self) -> [u8; 3] {
+        match self {
+            Self::Empty => [0, 0, 0],
+            Self::Wall => [128, 128, 128],
+            Self::Obstacle => [0, 255, 0],
+            Self::Vertical | Self::Horizontal | Self::Cross => [64, 64, 255],
+            Self::Guard(_) => [255, 0, 0],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Direction {
     Up,
@@ -56,6 +91,16 @@ enum Direction {
 }
 
 impl Direction {
+    fn parse(input: char) -> Option<Self> {
+        match input {
+            '^' => Some(Self::Up),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            '<' => Some(Self::Left),
+            _ => None,
+        }
+    }
+
     pub fn rotate(&self) -> Self {
         match self {
             Self::Up => Self::Right,
@@ -64,6 +109,24 @@ impl Direction {
             Self::Left => Self::Up,
         }
     }
+
+    fn bit(self) -> u8 {
+        match self {
+            Self::Up => 1,
+            Self::Right => 2,
+            Self::Down => 4,
+            Self::Left => 8,
+        }
+    }
+
+    fn to_char(self) -> char {
+        match self {
+            Self::Up => '^',
+            Self::Right => '>',
+            Self::Down => 'v',
+            Self::Left => '<',
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -72,23 +135,90 @@ struct Player {
     direction: Direction,
 }
 
-#[derive(Debug)]
+/// Nearest wall in `line` before (`forward` is false) or after `from`
+fn nearest_wall(line: &[i32], from: i32, forward: bool, extra: Option<i32>) -> Option<i32> {
+    if forward {
+        let idx = line.partition_point(|&w| w <= from);
+        let wall = line.get(idx).copied();
+
+        match extra {
+            Some(e) if e > from && wall.is_none_or(|w| e < w) => Some(e),
+            _ => wall,
+        }
+    } else {
+        let idx = line.partition_point(|&w| w < from);
+        let wall = idx.checked_sub(1).map(|i| line[i]);
+
+        match extra {
+            Some(e) if e < from && wall.is_none_or(|w| e > w) => Some(e),
+            _ => wall,
+        }
+    }
+}
+
+/// Sorted wall coordinates for every row and column
+#[derive(Debug, Clone)]
+struct JumpTable {
+    rows: Vec<Vec<i32>>,
+    cols: Vec<Vec<i32>>,
+}
+
+impl JumpTable {
+    fn new(walls: &HashSet<Position>, width: i32, height: i32) -> Self {
+        let mut rows = vec![vec![]; height as usize];
+        let mut cols = vec![vec![]; width as usize];
+
+        for wall in walls {
+            rows[wall.y as usize].push(wall.x);
+            cols[wall.x as usize].push(wall.y);
+        }
+
+        rows.iter_mut().for_each(|r| r.sort());
+        cols.iter_mut().for_each(|c| c.sort());
+
+        Self { rows, cols }
+    }
+
+    /// Cell where the guard stops before the next wall, `None` if it leaves the map
+    fn next_stop(&self, player: &Player, extra: Option<Position>) -> Option<Position> {
+        let Position { x, y } = player.position;
+        let in_col = extra.filter(|e| e.x == x).map(|e| e.y);
+        let in_row = extra.filter(|e| e.y == y).map(|e| e.x);
+
+        match player.direction {
+            Direction::Up => nearest_wall(&self.cols[x as usize], y, false, in_col)
+                .map(|wy| Position::new(x, wy + 1)),
+            Direction::Down => nearest_wall(&self.cols[x as usize], y, true, in_col)
+                .map(|wy| Position::new(x, wy - 1)),
+            Direction::Left => nearest_wall(&self.rows[y as usize], x, false, in_row)
+                .map(|wx| Position::new(wx + 1, y)),
+            Direction::Right => nearest_wall(&self.rows[y as usize], x, true, in_row)
+                .map(|wx| Position::new(wx - 1, y)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct Game {
     width: i32,
     height: i32,
     walls: HashSet<Position>,
+    jumps: JumpTable,
+    // every guard found in the map, the first one is the player
+    guards: Vec<Player>,
     player: Player,
     initial_player: Player,
-    moves: HashSet<Player>,
+    // direction bits of every visited cell, indexed by `idx`
+    visited: Vec<u8>,
     additional_wall: Option<Position>,
 }
 
 impl Game {
-    pub fn parse(input: &str) -> Self {
+    pub fn parse(input: &str) -> Result<Self> {
         let mut width = 0;
         let mut height = 0;
         let mut walls = HashSet::new();
-        let mut player: Option<Player> = None;
+        let mut guards = vec![];
 
         for (y, line) in input.lines().enumerate() {
             height += 1;
@@ -100,38 +230,60 @@ impl Game {
                         let point = Position::from_usize(x, y);
                         walls.insert(point);
                     }
-                    '^' => {
-                        let point = Position::from_usize(x, y);
-                        player = Some(Player {
-                            position: point,
-                            direction: Direction::Up,
-                        });
+                    c => {
+                        if let Some(direction) = Direction::parse(c) {
+                            guards.push(Player {
+                                position: Position::from_usize(x, y),
+                                direction,
+                            });
+                        }
                     }
-                    _ => {}
                 }
             }
         }
 
-        let player = player.expect("Player not found in map");
+        let player = guards
+            .first()
+            .cloned()
+            .ok_or_else(|| eyre!("Guard not found in map"))?;
         let initial_player = player.clone();
+        let jumps = JumpTable::new(&walls, width, height);
 
-        Game {
+        Ok(Game {
             width,
             height,
             walls,
+            jumps,
+            guards,
             player,
             initial_player,
-            moves: HashSet::new(),
+            visited: vec![0; (width * height) as usize],
             additional_wall: None,
-        }
+        })
     }
 
     pub fn reset(&mut self) {
-        self.player = self.initial_player.clone();
-        self.moves = HashSet::new();
+        self.start_from(self.initial_player.clone());
         self.additional_wall = None;
     }
 
+    fn start_from(&mut self, player: Player) {
+        self.player = player;
+        self.visited.fill(0);
+    }
+
+    fn idx(&self, position: Position) -> usize {
+        (position.y * self.width + position.x) as usize
+    }
+
+    fn is_inside(&self, position: Position) -> bool {
+        position.x >= 0 && position.x < self.width && position.y >= 0 && position.y < self.height
+    }
+
+    fn is_visited(&self, player: &Player) -> bool {
+        self.visited[self.idx(player.position)] & player.direction.bit() != 0
+    }
+
     pub fn tick(&mut self) {
         let next_point = self.player.position.to(self.player.direction);
 
@@ -144,22 +296,49 @@ impl Game {
 
     pub fn run(&mut self) -> RunResult {
         loop {
-            self.moves.insert(self.player.clone());
-            self.tick();
-            if self.is_out() {
-                return RunResult::Out;
+            if let Some(result) = self.step() {
+                return result;
             }
-            if self.moves.contains(&self.player) {
+        }
+    }
+
+    /// Same as `run`, but jumps straight to the next turn. Only turns are
+    /// recorded in `visited`, so `uniq_positions` is not valid afterwards.
+    pub fn run_jumps(&mut self) -> RunResult {
+        loop {
+            let Some(stop) = self.jumps.next_stop(&self.player, self.additional_wall) else {
+                return RunResult::Out;
+            };
+
+            self.player.position = stop;
+            self.player.direction = self.player.direction.rotate();
+
+            if self.is_visited(&self.player) {
                 return RunResult::Loop;
             }
+
+            let idx = self.idx(stop);
+            self.visited[idx] |= self.player.direction.bit();
+        }
+    }
+
+    /// One tick of `run`, returns the result once the guard leaves or loops
+    pub fn step(&mut self) -> Option<RunResult> {
+        let idx = self.idx(self.player.position);
+        self.visited[idx] |= self.player.direction.bit();
+        self.tick();
+
+        if self.is_out() {
+            Some(RunResult::Out)
+        } else if self.is_visited(&self.player) {
+            Some(RunResult::Loop)
+        } else {
+            None
         }
     }
 
     pub fn is_out(&self) -> bool {
-        self.player.position.x < 0
-            || self.player.position.x >= self.width
-            || self.player.position.y < 0
-            || self.player.position.y >= self.height
+        !self.is_inside(self.player.position)
     }
 
     fn have_vall(&self, point: &Position) -> bool {
@@ -172,41 +351,575 @@ impl Game {
     }
 
     pub fn uniq_positions(&self) -> usize {
-        let positions: HashSet<_> = self.moves.iter().map(|m| m.position).collect();
-        positions.len()
+        self.visited.iter().filter(|&&bits| bits != 0).count()
+    }
+
+    /// Every state of the original route, until the guard leaves or loops
+    pub fn route(&mut self) -> Vec<Player> {
+        self.reset();
+
+        let mut route = vec![];
+        loop {
+            route.push(self.player.clone());
+            if self.step().is_some() {
+                return route;
+            }
+        }
+    }
+
+    /// Only cells on the original route can change it. Each one is tried
+    /// from the state right before the guard first enters it.
+    pub fn loop_walls(&mut self) -> Vec<Position> {
+        self.loop_walls_with(Self::run_jumps)
+    }
+
+    fn loop_walls_with(&mut self, run: fn(&mut Self) -> RunResult) -> Vec<Position> {
+        let route = self.route();
+        let mut seen = vec![false; self.visited.len()];
+        let mut result = vec![];
+
+        seen[self.idx(self.initial_player.position)] = true;
+
+        for player in route {
+            let next = player.position.to(player.direction);
+            if !self.is_inside(next) || self.walls.contains(&next) || seen[self.idx(next)] {
+                continue;
+            }
+
+            seen[self.idx(next)] = true;
+
+            self.start_from(player);
+            self.additional_wall = Some(next);
+            if run(self) == RunResult::Loop {
+                result.push(next);
+            }
+        }
+
+        self.additional_wall = None;
+        result
+    }
+
+    fn visited(&self, position: Position, directions: [Direction; 2]) -> bool {
+        let bits = self.visited[self.idx(position)];
+        directions
+            .into_iter()
+            .any(|direction| bits & direction.bit() != 0)
+    }
+
+    fn cell(&self, position: Position, loops: &HashSet<Position>, with_guard: bool) -> Cell {
+        let vertical = self.visited(position, [Direction::Up, Direction::Down]);
+        let horizontal = self.visited(position, [Direction::Left, Direction::Right]);
+
+        if with_guard && !self.is_out() && self.player.position == position {
+            Cell::Guard(self.player.direction)
+        } else if self.initial_player.position == position {
+            Cell::Guard(self.initial_player.direction)
+        } else if self.walls.contains(&position) {
+            Cell::Wall
+        } else if loops.contains(&position) || self.additional_wall == Some(position) {
+            Cell::Obstacle
+        } else {
+            match (vertical, horizontal) {
+                (true, true) => Cell::Cross,
+                (true, false) => Cell::Vertical,
+                (false, true) => Cell::Horizontal,
+                (false, false) => Cell::Empty,
+            }
+        }
+    }
+
+    fn cells(&self, loops: &HashSet<Position>, with_guard: bool) -> Vec<Vec<Cell>> {
+        (0..self.height)
+            .map(|y| {
+                (0..self.width)
+                    .map(|x| self.cell(Position::new(x, y), loops, with_guard))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Route drawn as in the puzzle text, `loops` are marked with `O`
+    pub fn render(&self, loops: &HashSet<Position>, with_guard: bool) -> String {
+        self.cells(loops, with_guard)
+            .iter()
+            .map(|row| row.iter().map(|c| c.to_char()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    /// Same as `render`, as a binary PPM image with one pixel per cell
+    pub fn render_ppm(&self, loops: &HashSet<Position>, with_guard: bool) -> Vec<u8> {
+        let mut result = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+
+        for row in self.cells(loops, with_guard) {
+            for cell in row {
+                result.extend(cell.to_rgb());
+            }
+        }
+
+        result
+    }
+
+    /// Write one frame per tick of the original route into `dir`
+    pub fn export_frames(&mut self, dir: &Path, ppm: bool) -> Result<usize> {
+        fs::create_dir_all(dir)?;
+        self.reset();
+
+        let loops = HashSet::new();
+        let mut frame = 0;
+
+        loop {
+            let result = self.step();
+
+            if ppm {
+                let path = dir.join(format!("frame{:05}.ppm", frame));
+                fs::write(path, self.render_ppm(&loops, true))?;
+            } else {
+                let path = dir.join(format!("frame{:05}.txt", frame));
+                fs::write(path, self.render(&loops, true))?;
+            }
+
+            frame += 1;
+
+            if result.is_some() {
+                return Ok(frame);
+            }
+        }
     }
+}
 
-    pub fn possible_walls(&self) -> impl Iterator<Item = Position> {
-        let height = self.height;
-        let width = self.width;
-        let player = self.initial_player.clone();
-        let walls = self.walls.clone();
+#[derive(Debug, PartialEq)]
+enum GuardEnd {
+    Out(usize),
+    Loop(usize),
+}
+
+#[derive(Debug, PartialEq)]
+struct Collision {
+    tick: usize,
+    position: Position,
+    guards: Vec<usize>,
+}
+
+/// Two guards passing through each other between two ticks
+#[derive(Debug, PartialEq)]
+struct Swap {
+    tick: usize,
+    guards: [usize; 2],
+    positions: [Position; 2],
+}
 
-        (0..height)
-            .flat_map(move |y| (0..width).map(move |x| Position::new(x, y)))
-            .filter(move |p| !walls.contains(p) && &player.position != p)
+#[derive(Debug)]
+struct PatrolReport {
+    ends: Vec<GuardEnd>,
+    collisions: Vec<Collision>,
+    swaps: Vec<Swap>,
+    // groups of guards stuck in the same loop
+    shared_loops: Vec<Vec<usize>>,
+}
+
+/// Several guards moving at the same time, every one with its own `Game`.
+/// Guards don't block each other, meeting on the same cell is a collision.
+/// A looping guard keeps walking its cycle until every guard is done.
+#[derive(Debug)]
+struct Patrol {
+    games: Vec<Game>,
+}
+
+impl Patrol {
+    pub fn new(game: &Game) -> Self {
+        let games = game
+            .guards
+            .iter()
+            .map(|guard| {
+                let mut game = game.clone();
+                game.initial_player = guard.clone();
+                game.reset();
+                game
+            })
+            .collect();
+
+        Self { games }
+    }
+
+    pub fn run(&mut self) -> PatrolReport {
+        let total = self.games.len();
+        let mut ends: Vec<Option<GuardEnd>> = (0..total).map(|_| None).collect();
+        let mut routes = self
+            .games
+            .iter()
+            .map(|g| vec![g.player.clone()])
+            .collect::<Vec<_>>();
+        let mut cycles: Vec<Option<Vec<Player>>> = vec![None; total];
+        let mut collisions = vec![];
+        let mut swaps = vec![];
+        let mut previous = self
+            .games
+            .iter()
+            .map(|g| Some(g.player.position))
+            .collect::<Vec<_>>();
+        let mut tick = 0;
+
+        while ends.iter().any(Option::is_none) {
+            tick += 1;
+
+            for (idx, game) in self.games.iter_mut().enumerate() {
+                if ends[idx].is_some() {
+                    continue;
+                }
+
+                match game.step() {
+                    Some(RunResult::Out) => ends[idx] = Some(GuardEnd::Out(tick)),
+                    Some(RunResult::Loop) => {
+                        let route = &routes[idx];
+                        let start = route
+                            .iter()
+                            .position(|p| p == &game.player)
+                            .expect("Loop start is on the route");
+                        cycles[idx] = Some(route[start..].to_vec());
+                        ends[idx] = Some(GuardEnd::Loop(tick));
+                    }
+                    None => routes[idx].push(game.player.clone()),
+                }
+            }
+
+            let current = (0..total)
+                .map(|idx| match (&ends[idx], &cycles[idx]) {
+                    (Some(GuardEnd::Out(_)), _) => None,
+                    (Some(GuardEnd::Loop(start)), Some(cycle)) => {
+                        Some(cycle[(tick - start) % cycle.len()].position)
+                    }
+                    _ => Some(self.games[idx].player.position),
+                })
+                .collect::<Vec<_>>();
+
+            for a in 0..total {
+                for b in a + 1..total {
+                    let (Some(prev_a), Some(prev_b), Some(cur_a), Some(cur_b)) =
+                        (previous[a], previous[b], current[a], current[b])
+                    else {
+                        continue;
+                    };
+
+                    if cur_a != cur_b && prev_a == cur_b && prev_b == cur_a {
+                        swaps.push(Swap {
+                            tick,
+                            guards: [a, b],
+                            positions: [cur_a, cur_b],
+                        });
+                    }
+                }
+            }
+
+            let mut positions: Vec<(Position, Vec<usize>)> = vec![];
+            for (idx, position) in current.iter().enumerate() {
+                let Some(position) = *position else {
+                    continue;
+                };
+
+                match positions.iter_mut().find(|(p, _)| p == &position) {
+                    Some((_, guards)) => guards.push(idx),
+                    None => positions.push((position, vec![idx])),
+                }
+            }
+
+            collisions.extend(
+                positions
+                    .into_iter()
+                    .filter(|(_, guards)| guards.len() > 1)
+                    .map(|(position, guards)| Collision {
+                        tick,
+                        position,
+                        guards,
+                    }),
+            );
+
+            previous = current;
+        }
+
+        let mut shared_loops: Vec<Vec<usize>> = vec![];
+        for idx in 0..total {
+            let Some(cycle) = &cycles[idx] else {
+                continue;
+            };
+
+            let group = shared_loops.iter_mut().find(|group| {
+                cycles[group[0]]
+                    .as_ref()
+                    .is_some_and(|c| c.contains(&cycle[0]))
+            });
+
+            match group {
+                Some(group) => group.push(idx),
+                None => shared_loops.push(vec![idx]),
+            }
+        }
+        shared_loops.retain(|group| group.len() > 1);
+
+        PatrolReport {
+            ends: ends
+                .into_iter()
+                .map(|e| e.expect("All guards done"))
+                .collect(),
+            collisions,
+            swaps,
+            shared_loops,
+        }
     }
 }
 
+fn bench(game: &mut Game) {
+    let now = Instant::now();
+    let ticks = game.loop_walls_with(Game::run);
+    let ticks_elapsed = now.elapsed();
+
+    let now = Instant::now();
+    let jumps = game.loop_walls_with(Game::run_jumps);
+    let jumps_elapsed = now.elapsed();
+
+    assert_eq!(ticks, jumps);
+
+    println!("run:       {:?}", ticks_elapsed);
+    println!("run_jumps: {:?}", jumps_elapsed);
+}
+
 fn main() -> Result<()> {
     let data = std::fs::read_to_string("data/day06.txt")?;
-    let mut game = Game::parse(&data);
-    let r = game.run();
-
-    dbg!(r);
-    println!("{}", game.uniq_positions());
-
-    let abc = game
-        .possible_walls()
-        .map(|p| {
-            game.reset();
-            game.additional_wall = Some(p);
-            game.run()
-        })
-        .filter(|r| r == &RunResult::Loop)
-        .count();
+    let mut game = Game::parse(&data)?;
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.iter().any(|arg| arg == "--guards") {
+        let report = Patrol::new(&game).run();
+        for (idx, end) in report.ends.iter().enumerate() {
+            println!("guard {}: {:?}", idx, end);
+        }
+        for collision in report.collisions.iter() {
+            println!("collision: {:?}", collision);
+        }
+        for swap in report.swaps.iter() {
+            println!("swap: {:?}", swap);
+        }
+        for group in report.shared_loops.iter() {
+            println!("shared loop: {:?}", group);
+        }
+
+        return Ok(());
+    }
+
+    if let Some(idx) = args.iter().position(|arg| arg == "--frames") {
+        let dir = args.get(idx + 1).map(String::as_str).unwrap_or("frames");
+        let ppm = args.iter().any(|arg| arg == "--ppm");
+        let total = game.export_frames(Path::new(dir), ppm)?;
+        println!("{} frames written to {}", total, dir);
+
+        return Ok(());
+    }
+
+    if args.iter().any(|arg| arg == "--bench") {
+        bench(&mut game);
+        return Ok(());
+    }
 
-    dbg!(abc);
+    game.run();
+    println!("A: {}", game.uniq_positions());
+
+    let loops = game.loop_walls();
+    println!("B: {}", loops.len());
+
+    if args.iter().any(|arg| arg == "--render") {
+        game.reset();
+        game.run();
+        print!("{}", game.render(&loops.into_iter().collect(), false));
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn test_parse_directions() {
+        let game = Game::parse("...\n.>.\n...").unwrap();
+        assert_eq!(game.player.direction, Direction::Right);
+
+        let game = Game::parse("v..\n..<").unwrap();
+        assert_eq!(game.guards.len(), 2);
+        assert_eq!(game.guards[1].direction, Direction::Left);
+        assert_eq!(game.guards[1].position, Position::new(2, 1));
+    }
+
+    #[test]
+    fn test_parse_no_guard() {
+        let error = Game::parse("..#\n...").unwrap_err();
+        assert_eq!(error.to_string(), "Guard not found in map");
+    }
+
+    #[test]
+    fn test_patrol_collision() {
+        let game = Game::parse(">...<").unwrap();
+        let report = Patrol::new(&game).run();
+
+        assert_eq!(report.ends, [GuardEnd::Out(5), GuardEnd::Out(5)]);
+        assert_eq!(
+            report.collisions,
+            [Collision {
+                tick: 2,
+                position: Position::new(2, 0),
+                guards: vec![0, 1]
+            }]
+        );
+        assert!(report.swaps.is_empty());
+        assert!(report.shared_loops.is_empty());
+
+        // with an odd gap the guards pass through each other
+        let game = Game::parse(">..<").unwrap();
+        let report = Patrol::new(&game).run();
+
+        assert_eq!(report.ends, [GuardEnd::Out(4), GuardEnd::Out(4)]);
+        assert!(report.collisions.is_empty());
+        assert_eq!(
+            report.swaps,
+            [Swap {
+                tick: 2,
+                guards: [0, 1],
+                positions: [Position::new(2, 0), Position::new(1, 0)]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_patrol_looping_guard_moves() {
+        // guard 0 loops from tick 10, guard 1 joins its loop later and
+        // meets it only where the cycle really puts it
+        let map = ".#..........
+....#.......
+#^.........<
+...#........";
+        let game = Game::parse(map).unwrap();
+        let report = Patrol::new(&game).run();
+
+        assert_eq!(report.ends, [GuardEnd::Loop(10), GuardEnd::Loop(18)]);
+        assert_eq!(
+            report
+                .collisions
+                .iter()
+                .map(|c| (c.tick, c.position))
+                .collect::<Vec<_>>(),
+            [
+                (10, Position::new(1, 2)),
+                (12, Position::new(1, 1)),
+                (15, Position::new(3, 1)),
+                (17, Position::new(3, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_patrol_shared_loop() {
+        let map = ".#....
+.^..v#
+#.....
+....#.";
+        let game = Game::parse(map).unwrap();
+        let report = Patrol::new(&game).run();
+
+        assert!(matches!(
+            report.ends[..],
+            [GuardEnd::Loop(_), GuardEnd::Loop(_)]
+        ));
+        assert!(report.collisions.is_empty());
+        assert_eq!(report.shared_loops, [vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_loop_walls() {
+        let mut game = Game::parse(EXAMPLE).unwrap();
+        let loops = game.loop_walls();
+
+        assert_eq!(loops.len(), 6);
+        assert!(loops.contains(&Position::new(3, 6)));
+    }
+
+    #[test]
+    fn test_loop_walls_jumps() {
+        let mut game = Game::parse(EXAMPLE).unwrap();
+
+        let ticks = game.loop_walls_with(Game::run);
+        let jumps = game.loop_walls_with(Game::run_jumps);
+        assert_eq!(ticks, jumps);
+    }
+
+    #[test]
+    fn test_next_stop() {
+        let game = Game::parse(EXAMPLE).unwrap();
+        let player = game.initial_player.clone();
+
+        assert_eq!(
+            game.jumps.next_stop(&player, None),
+            Some(Position::new(4, 1))
+        );
+        assert_eq!(
+            game.jumps.next_stop(&player, Some(Position::new(4, 3))),
+            Some(Position::new(4, 4))
+        );
+
+        let right = Player {
+            position: Position::new(4, 9),
+            direction: Direction::Right,
+        };
+        assert_eq!(
+            game.jumps.next_stop(&right, None),
+            Some(Position::new(5, 9))
+        );
+
+        let left = Player {
+            position: Position::new(4, 9),
+            direction: Direction::Left,
+        };
+        assert_eq!(game.jumps.next_stop(&left, None), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut game = Game::parse(EXAMPLE).unwrap();
+        game.additional_wall = Some(Position::new(3, 6));
+        assert_eq!(game.run(), RunResult::Loop);
+
+        let expected = "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+";
+        assert_eq!(game.render(&HashSet::new(), false), expected);
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let mut game = Game::parse(EXAMPLE).unwrap();
+        game.run();
+
+        let image = game.render_ppm(&HashSet::new(), true);
+        let header = b"P6\n10 10\n255\n";
+
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 10 * 10 * 3);
+    }
+}
@@ -1,40 +1,219 @@
-fn num_of_digits(mut input: i64) -> i64 {
+use std::{fmt::Display, time::Instant};
+
+fn num_of_digits(mut input: i64, base: i64) -> i64 {
     let mut result = 1;
-    while input > 9 {
-        input /= 10;
+    while input >= base {
+        input /= base;
         result += 1;
     }
 
     result
 }
 
-fn concat(a: i64, b: i64) -> i64 {
-    let d = num_of_digits(b);
-    a * 10_i64.pow(d as u32) + b
+/// `None` on overflow, same as for all operators. Negative operands have no
+/// digits to join, so they are rejected as well
+fn concat_base(a: i64, b: i64, base: u32) -> Option<i64> {
+    if a < 0 || b < 0 {
+        return None;
+    }
+
+    let base = base as i64;
+    let d = num_of_digits(b, base);
+
+    match base.checked_pow(d as u32) {
+        Some(shift) => a.checked_mul(shift).and_then(|a| a.checked_add(b)),
+        None => (a == 0).then_some(b),
+    }
+}
+
+fn concat(a: i64, b: i64) -> Option<i64> {
+    concat_base(a, b, 10)
+}
+
+/// Inverse of `concat_base`, so negative numbers are rejected the same way
+fn strip_suffix(result: i64, b: i64, base: u32) -> Option<i64> {
+    if result < 0 || b < 0 {
+        return None;
+    }
+
+    let base = base as i64;
+
+    match base.checked_pow(num_of_digits(b, base) as u32) {
+        Some(shift) => (result % shift == b).then(|| result / shift),
+        // the shift doesn't fit, so only zero can be in front of `b`
+        None => (result == b).then_some(0),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Mul,
+    Sub,
+    Xor,
+    Concat,
+    ConcatBase(u32),
+}
+
+impl Operator {
+    fn parse(input: &str) -> Option<Self> {
+        match input {
+            "+" => Some(Self::Add),
+            "*" => Some(Self::Mul),
+            "-" => Some(Self::Sub),
+            "^" => Some(Self::Xor),
+            "||" => Some(Self::Concat),
+            _ => input
+                .strip_prefix("||")
+                .and_then(|base| base.parse().ok())
+                .filter(|&base| base >= 2)
+                .map(Self::ConcatBase),
+        }
+    }
+
+    /// `None` on overflow, which is treated as exceeding any target
+    fn apply(self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            Self::Add => a.checked_add(b),
+            Self::Mul => a.checked_mul(b),
+            Self::Sub => a.checked_sub(b),
+            Self::Xor => Some(a ^ b),
+            Self::Concat => concat(a, b),
+            Self::ConcatBase(base) => concat_base(a, b, base),
+        }
+    }
+
+    /// The left operand that gives `result` with `b` on the right
+    fn undo(self, result: i64, b: i64) -> Option<i64> {
+        match self {
+            Self::Add => result.checked_sub(b),
+            Self::Mul => (b != 0 && result.checked_rem(b) == Some(0))
+                .then(|| result.checked_div(b))
+                .flatten(),
+            Self::Sub => result.checked_add(b),
+            Self::Xor => Some(result ^ b),
+            Self::Concat => strip_suffix(result, b, 10),
+            Self::ConcatBase(base) => strip_suffix(result, b, base),
+        }
+    }
+
+    /// Never decreases the left operand for non-negative numbers
+    fn is_growing(self) -> bool {
+        matches!(
+            self,
+            Self::Add | Self::Mul | Self::Concat | Self::ConcatBase(_)
+        )
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Mul => write!(f, "*"),
+            Self::Sub => write!(f, "-"),
+            Self::Xor => write!(f, "^"),
+            Self::Concat => write!(f, "||"),
+            Self::ConcatBase(base) => write!(f, "||{base}"),
+        }
+    }
 }
 
-fn is_match_rest(target: i64, a: i64, b: &[i64], use_concat: bool) -> bool {
+/// Search for operators applied left to right, the found ones are pushed to `used`
+fn solve_rest(
+    target: i64,
+    a: i64,
+    b: &[i64],
+    operators: &[Operator],
+    prune: bool,
+    used: &mut Vec<Operator>,
+) -> bool {
     if b.is_empty() {
         return target == a;
     }
 
-    if a > target {
+    if prune && a > target {
         return false;
     }
 
-    let sum = a + b[0];
-    let mul = a * b[0];
+    for &operator in operators {
+        let Some(next) = operator.apply(a, b[0]) else {
+            continue;
+        };
+
+        used.push(operator);
+        if solve_rest(target, next, &b[1..], operators, prune, used) {
+            return true;
+        }
+        used.pop();
+    }
+
+    false
+}
 
-    if use_concat {
-        let contcat = concat(a, b[0]);
+/// Any operators that evaluate the numbers without overflow, pushed to `used`
+fn evaluate_any(a: i64, b: &[i64], operators: &[Operator], used: &mut Vec<Operator>) -> bool {
+    let Some((&next, rest)) = b.split_first() else {
+        return true;
+    };
 
-        is_match_rest(target, sum, &b[1..], use_concat)
-            || is_match_rest(target, mul, &b[1..], use_concat)
-            || is_match_rest(target, contcat, &b[1..], use_concat)
-    } else {
-        is_match_rest(target, sum, &b[1..], use_concat)
-            || is_match_rest(target, mul, &b[1..], use_concat)
+    for &operator in operators {
+        if let Some(a) = operator.apply(a, next) {
+            used.push(operator);
+            if evaluate_any(a, rest, operators, used) {
+                return true;
+            }
+            used.pop();
+        }
+    }
+
+    false
+}
+
+/// Search from the target back to the first number, undoing the last operand
+/// each time. Exact division and suffix stripping fail for almost every
+/// operand, so most branches die right away. Operators are pushed to `used`
+/// from the last one.
+fn solve_reverse_rest(
+    target: i64,
+    b: &[i64],
+    operators: &[Operator],
+    prune: bool,
+    used: &mut Vec<Operator>,
+) -> bool {
+    let (&last, rest) = b.split_last().expect("At least one number");
+    if rest.is_empty() {
+        return target == last;
+    }
+
+    if prune && target < 0 {
+        return false;
+    }
+
+    for &operator in operators {
+        // anything multiplied by zero is zero, any operators before it will do
+        // as long as they don't overflow
+        if operator == Operator::Mul && last == 0 && target == 0 {
+            let (&first, middle) = rest.split_first().expect("At least one number");
+            let mut prefix = vec![];
+
+            if evaluate_any(first, middle, operators, &mut prefix) {
+                used.push(operator);
+                used.extend(prefix.iter().rev());
+                return true;
+            }
+        }
+
+        if let Some(prev) = operator.undo(target, last) {
+            used.push(operator);
+            if solve_reverse_rest(prev, rest, operators, prune, used) {
+                return true;
+            }
+            used.pop();
+        }
     }
+
+    false
 }
 
 #[derive(Debug)]
@@ -55,15 +234,56 @@ impl Equation {
         Self(first, rest)
     }
 
-    pub fn is_match(&self, use_concat: bool) -> bool {
-        match self.1.len() {
-            0 => false,
-            1 => self.0 == self.1[0],
-            _ => is_match_rest(self.0, self.1[0], &self.1[1..], use_concat),
+    /// Operators between the numbers that give the target, if any
+    pub fn solve(&self, operators: &[Operator]) -> Option<Vec<Operator>> {
+        if self.1.is_empty() {
+            return None;
+        }
+
+        let prune = self.is_prunable(operators);
+        let mut used = vec![];
+
+        if solve_reverse_rest(self.0, &self.1, operators, prune, &mut used) {
+            used.reverse();
+            Some(used)
+        } else {
+            None
         }
     }
+
+    /// Same as `solve`, searching left to right
+    pub fn solve_forward(&self, operators: &[Operator]) -> Option<Vec<Operator>> {
+        let (&first, rest) = self.1.split_first()?;
+        let prune = self.is_prunable(operators);
+        let mut used = vec![];
+
+        solve_rest(self.0, first, rest, operators, prune, &mut used).then_some(used)
+    }
+
+    fn is_prunable(&self, operators: &[Operator]) -> bool {
+        operators.iter().all(|o| o.is_growing()) && self.1.iter().all(|&n| n > 0)
+    }
+
+    /// `None` if any step overflows
+    pub fn evaluate(&self, operators: &[Operator]) -> Option<i64> {
+        operators
+            .iter()
+            .zip(self.1[1..].iter())
+            .try_fold(self.1[0], |a, (operator, &b)| operator.apply(a, b))
+    }
+
+    pub fn format(&self, operators: &[Operator]) -> String {
+        let mut result = format!("{}: {}", self.0, self.1[0]);
+        for (operator, number) in operators.iter().zip(self.1[1..].iter()) {
+            result.push_str(&format!(" {} {}", operator, number));
+        }
+
+        result
+    }
 }
 
+type SolveFn = fn(&Equation, &[Operator]) -> Option<Vec<Operator>>;
+
 #[derive(Debug)]
 struct Game(Vec<Equation>);
 
@@ -74,26 +294,81 @@ impl Game {
         Self(inner)
     }
 
-    pub fn solve_a(&self) -> i64 {
-        self.0
-            .iter()
-            .filter(|e| e.is_match(false))
-            .map(|e| e.0)
-            .sum()
+    /// The sum of many targets may not fit into `i64`
+    pub fn solve(&self, operators: &[Operator]) -> i128 {
+        self.solve_with(operators, Equation::solve)
     }
 
-    pub fn solve_b(&self) -> i64 {
+    fn solve_with(&self, operators: &[Operator], solve: SolveFn) -> i128 {
         self.0
             .iter()
-            .filter(|e| e.is_match(true))
-            .map(|e| e.0)
+            .filter(|e| solve(e, operators).is_some())
+            .map(|e| e.0 as i128)
             .sum()
     }
+
+    pub fn solve_a(&self) -> i128 {
+        self.solve(&[Operator::Add, Operator::Mul])
+    }
+
+    pub fn solve_b(&self) -> i128 {
+        self.solve(&[Operator::Add, Operator::Mul, Operator::Concat])
+    }
+}
+
+fn bench(game: &Game) {
+    let sets = [
+        ("A", vec![Operator::Add, Operator::Mul]),
+        ("B", vec![Operator::Add, Operator::Mul, Operator::Concat]),
+    ];
+
+    for (name, operators) in sets.iter() {
+        let now = Instant::now();
+        let forward = game.solve_with(operators, Equation::solve_forward);
+        let forward_elapsed = now.elapsed();
+
+        let now = Instant::now();
+        let reverse = game.solve_with(operators, Equation::solve);
+        let reverse_elapsed = now.elapsed();
+
+        assert_eq!(forward, reverse);
+
+        println!("{} forward: {:?}", name, forward_elapsed);
+        println!("{} reverse: {:?}", name, reverse_elapsed);
+    }
 }
 
 fn main() {
     let data = std::fs::read_to_string("data/day07.txt").unwrap();
     let game = Game::parse(&data);
+    let args = std::env::args().collect::<Vec<_>>();
+
+    if args.iter().any(|arg| arg == "--bench") {
+        bench(&game);
+        return;
+    }
+
+    // e.g. `--ops "+ * - ^ || ||2"` prints every solved equation
+    if let Some(idx) = args.iter().position(|arg| arg == "--ops") {
+        let operators = args
+            .get(idx + 1)
+            .map(|list| {
+                list.split_whitespace()
+                    .map(|op| Operator::parse(op).expect("Invalid operator"))
+                    .collect::<Vec<_>>()
+            })
+            .expect("Missing operators");
+
+        for equation in game.0.iter() {
+            if let Some(used) = equation.solve(&operators) {
+                assert_eq!(equation.evaluate(&used), Some(equation.0));
+                println!("{}", equation.format(&used));
+            }
+        }
+        println!("Total: {}", game.solve(&operators));
+
+        return;
+    }
 
     let a = game.solve_a();
     let b = game.solve_b();
@@ -105,8 +380,198 @@ fn main() {
 mod test {
     use super::*;
 
+    const EXAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20";
+
     #[test]
     fn test_concat() {
-        assert_eq!(1234, concat(12, 34));
+        assert_eq!(Some(1234), concat(12, 34));
+    }
+
+    #[test]
+    fn test_concat_base() {
+        assert_eq!(Some(0b1011), concat_base(0b10, 0b11, 2));
+        assert_eq!(Some(0x1af), concat_base(0x1a, 0xf, 16));
+        assert_eq!(Some(100), concat_base(10, 0, 10));
+    }
+
+    #[test]
+    fn test_operator_parse() {
+        assert_eq!(Operator::parse("||"), Some(Operator::Concat));
+        assert_eq!(Operator::parse("||16"), Some(Operator::ConcatBase(16)));
+        assert_eq!(Operator::parse("||1"), None);
+        assert_eq!(Operator::parse("/"), None);
+    }
+
+    #[test]
+    fn test_example() {
+        let game = Game::parse(EXAMPLE);
+
+        assert_eq!(game.solve_a(), 3749);
+        assert_eq!(game.solve_b(), 11387);
+    }
+
+    #[test]
+    fn test_solve_operators() {
+        use Operator::*;
+
+        let equation = Equation::parse("7290: 6 8 6 15");
+        let operators = equation.solve(&[Add, Mul, Concat]).unwrap();
+
+        assert_eq!(operators, [Mul, Concat, Mul]);
+        assert_eq!(equation.evaluate(&operators), Some(7290));
+        assert_eq!(equation.format(&operators), "7290: 6 * 8 || 6 * 15");
+        assert_eq!(equation.solve(&[Add, Mul]), None);
+    }
+
+    #[test]
+    fn test_solve_zero() {
+        use Operator::*;
+
+        let equation = Equation::parse("0: 7 3 0");
+        let operators = equation.solve(&[Add, Mul]).unwrap();
+        assert_eq!(equation.evaluate(&operators), Some(0));
+    }
+
+    #[test]
+    fn test_solve_extra_operators() {
+        use Operator::*;
+
+        // subtraction can't be pruned by `a > target`
+        let equation = Equation::parse("5: 10 3 2");
+        assert_eq!(equation.solve(&[Add, Sub]), Some(vec![Sub, Sub]));
+
+        let equation = Equation::parse("6: 5 3");
+        assert_eq!(equation.solve(&[Add, Xor]), Some(vec![Xor]));
+
+        let equation = Equation::parse("11: 2 3");
+        assert_eq!(
+            equation.solve(&[Add, ConcatBase(2)]),
+            Some(vec![ConcatBase(2)])
+        );
+    }
+
+    /// xorshift, enough to generate random equations
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, max: i64) -> i64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % max as u64) as i64
+        }
+    }
+
+    #[test]
+    fn test_reverse_agrees_with_forward() {
+        use Operator::*;
+
+        let sets = [
+            vec![Add, Mul],
+            vec![Add, Mul, Concat],
+            vec![Add, Sub, Xor],
+            vec![Mul, ConcatBase(2), Add],
+            vec![Add, Sub, Concat],
+        ];
+        let mut random = Random(0x2024);
+
+        for _ in 0..2000 {
+            let operators = &sets[random.next(sets.len() as i64) as usize];
+            let len = 1 + random.next(6) as usize;
+            let numbers = (0..len).map(|_| random.next(20)).collect::<Vec<_>>();
+
+            // half of the targets are reachable by construction
+            let target = if random.next(2) == 0 {
+                random.next(1000)
+            } else {
+                numbers[1..].iter().fold(numbers[0], |a, &b| {
+                    let operator = operators[random.next(operators.len() as i64) as usize];
+                    // every set has `Add`, for concat of a negative number
+                    operator.apply(a, b).unwrap_or(a + b)
+                })
+            };
+
+            let equation = Equation(target, numbers);
+            let forward = equation.solve_forward(operators);
+            let reverse = equation.solve(operators);
+
+            assert_eq!(forward.is_some(), reverse.is_some(), "{:?}", equation);
+            if let Some(used) = reverse {
+                assert_eq!(equation.evaluate(&used), Some(target), "{:?}", equation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(concat(i64::MAX / 10 + 1, 5), None);
+        assert_eq!(concat(0, i64::MAX), Some(i64::MAX));
+        assert_eq!(Operator::Mul.apply(i64::MAX / 2 + 1, 2), None);
+        assert_eq!(Operator::Add.apply(i64::MAX, 1), None);
+        assert_eq!(Operator::Sub.apply(i64::MIN, 1), None);
+        assert_eq!(Operator::Add.undo(i64::MIN, 1), None);
+        assert_eq!(Operator::Mul.undo(i64::MIN, -1), None);
+        assert_eq!(strip_suffix(i64::MAX, i64::MAX, 10), Some(0));
+    }
+
+    #[test]
+    fn test_concat_negative() {
+        use Operator::*;
+
+        assert_eq!(concat(-3, 5), None);
+        assert_eq!(concat(3, -5), None);
+        assert_eq!(strip_suffix(-35, 5, 10), None);
+
+        let equation = Equation::parse("-25: 2 5 5");
+        assert_eq!(equation.solve(&[Sub, Concat]), None);
+        assert_eq!(equation.solve_forward(&[Sub, Concat]), None);
+
+        let equation = Equation::parse("-10: 2 5 35");
+        assert_eq!(equation.solve(&[Sub, Concat]), Some(vec![Concat, Sub]));
+        assert_eq!(
+            equation.solve_forward(&[Sub, Concat]),
+            Some(vec![Concat, Sub])
+        );
+    }
+
+    #[test]
+    fn test_solve_near_max() {
+        use Operator::*;
+
+        let operators = [Add, Mul, Concat];
+        let cases = [
+            (Equation(i64::MAX, vec![i64::MAX - 1, 1]), Some(vec![Add])),
+            (Equation(i64::MAX, vec![i64::MAX / 2 + 1, 2]), None),
+            (
+                Equation(i64::MAX - 1, vec![i64::MAX / 2, 2]),
+                Some(vec![Mul]),
+            ),
+            (
+                Equation(i64::MAX, vec![i64::MAX / 10, 7]),
+                Some(vec![Concat]),
+            ),
+            (Equation(i64::MAX, vec![i64::MAX / 10, 8]), None),
+            (Equation(5, vec![i64::MAX, i64::MAX, 5]), None),
+            (Equation(0, vec![i64::MAX, i64::MAX, 0]), None),
+            (Equation(0, vec![i64::MAX, 1, 0]), Some(vec![Mul, Mul])),
+        ];
+
+        for (equation, expected) in cases {
+            assert_eq!(equation.solve(&operators), expected, "{:?}", equation);
+            assert_eq!(
+                equation.solve_forward(&operators),
+                expected,
+                "{:?}",
+                equation
+            );
+        }
     }
 }
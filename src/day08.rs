@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    fmt::Display,
     ops::{Add, Sub},
 };
 
@@ -15,11 +16,20 @@ struct PointIter {
 
 impl PointIter {
     fn new(a: &Point, b: &Point) -> Self {
+        Self::with_step(a, a.diff(b))
+    }
+
+    /// Every grid point on the line through `a` and `b`, going past `b`
+    fn reduced(a: &Point, b: &Point) -> Self {
+        Self::with_step(a, a.diff(b).reduced())
+    }
+
+    fn with_step(a: &Point, step: Vector) -> Self {
         Self {
             x0: a.x,
             y0: a.y,
-            x_step: b.x - a.x,
-            y_step: b.y - a.y,
+            x_step: step.x,
+            y_step: step.y,
             n: 1,
         }
     }
@@ -121,19 +131,19 @@ impl Sub<Vector> for Point {
 
 #[derive(Debug)]
 struct BBox {
-    x: i32,
-    y: i32,
+    width: i32,
+    height: i32,
 }
 impl BBox {
-    fn new(x: usize, y: usize) -> Self {
+    fn new(width: usize, height: usize) -> Self {
         Self {
-            x: x as i32,
-            y: y as i32,
+            width: width as i32,
+            height: height as i32,
         }
     }
 
     fn within(&self, p: &Point) -> bool {
-        p.x >= 0 && p.x <= self.x && p.y >= 0 && p.y <= self.y
+        p.x >= 0 && p.x < self.width && p.y >= 0 && p.y < self.height
     }
 }
 
@@ -147,6 +157,42 @@ impl Vector {
     fn new(x: i32, y: i32) -> Self {
         Self { x, y }
     }
+
+    /// Smallest step in the same direction that stays on the grid
+    fn reduced(&self) -> Self {
+        let d = gcd(self.x.abs(), self.y.abs()).max(1);
+        Self::new(self.x / d, self.y / d)
+    }
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+type AntinodesFn = fn(&Game, &[Point]) -> HashSet<Point>;
+
+#[derive(Debug, PartialEq, Eq)]
+struct FreqStats {
+    freq: Freq,
+    antennas: usize,
+    pairs: usize,
+    antinodes: usize,
+    /// Antinodes also produced by another frequency
+    shared: usize,
+}
+
+impl Display for FreqStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} antennas, {} pairs, {} antinodes, {} shared",
+            self.freq.0, self.antennas, self.pairs, self.antinodes, self.shared
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -157,14 +203,14 @@ struct Game {
 
 impl Game {
     fn parse(input: &str) -> Self {
-        let mut max_x = 0;
-        let mut max_y = 0;
+        let mut width = 0;
+        let mut height = 0;
         let mut antennas: HashMap<Freq, Vec<Point>> = HashMap::new();
 
         for (y, line) in input.lines().enumerate() {
-            max_y = max_y.max(y);
+            height = height.max(y + 1);
             for (x, sym) in line.chars().enumerate() {
-                max_x = max_x.max(x);
+                width = width.max(x + 1);
                 let point = Point::new(x, y);
                 match sym {
                     '.' => {}
@@ -176,44 +222,111 @@ impl Game {
             }
         }
 
-        let b_box = BBox::new(max_x, max_y);
+        let b_box = BBox::new(width, height);
 
         Self { b_box, antennas }
     }
 
-    fn solve_a(&self) -> usize {
-        let total: HashSet<Point> = self
-            .antennas
-            .values()
-            .flat_map(|a| PairsIter::new(a))
+    fn freq_antinodes_a(&self, antennas: &[Point]) -> HashSet<Point> {
+        PairsIter::new(antennas)
             .flat_map(|(a, b)| {
+                // the first point is the other antenna, the antinode is one step past it
                 let s1 = PointIter::new(a, b)
-                    .take_while(|p| self.b_box.within(p))
-                    .take(1);
+                    .skip(1)
+                    .take(1)
+                    .filter(|p| self.b_box.within(p));
                 let s2 = PointIter::new(b, a)
-                    .take_while(|p| self.b_box.within(p))
-                    .take(1);
+                    .skip(1)
+                    .take(1)
+                    .filter(|p| self.b_box.within(p));
                 s1.chain(s2)
             })
-            .collect();
-
-        total.len()
+            .collect()
     }
 
-    fn solve_b(&self) -> usize {
-        let total: HashSet<Point> = self
-            .antennas
-            .values()
-            .flat_map(|a| PairsIter::new(a))
+    fn freq_antinodes_b(&self, antennas: &[Point]) -> HashSet<Point> {
+        PairsIter::new(antennas)
             .flat_map(|(a, b)| {
-                let s1 = PointIter::new(a, b).take_while(|p| self.b_box.within(p));
-                let s2 = PointIter::new(b, a).take_while(|p| self.b_box.within(p));
+                let s1 = PointIter::reduced(a, b).take_while(|p| self.b_box.within(p));
+                let s2 = PointIter::reduced(b, a).take_while(|p| self.b_box.within(p));
                 s1.chain(s2)
             })
+            .collect()
+    }
+
+    fn antinodes_a(&self) -> HashSet<Point> {
+        self.antennas
+            .values()
+            .flat_map(|a| self.freq_antinodes_a(a))
+            .collect()
+    }
+
+    fn antinodes_b(&self) -> HashSet<Point> {
+        self.antennas
+            .values()
+            .flat_map(|a| self.freq_antinodes_b(a))
+            .collect()
+    }
+
+    /// Per frequency statistics, most productive frequencies first
+    fn report_with(&self, antinodes: AntinodesFn) -> Vec<FreqStats> {
+        let per_freq = self
+            .antennas
+            .iter()
+            .map(|(freq, points)| (*freq, points, antinodes(self, points)))
+            .collect::<Vec<_>>();
+
+        let mut owners: HashMap<Point, usize> = HashMap::new();
+        for point in per_freq.iter().flat_map(|(_, _, nodes)| nodes) {
+            *owners.entry(*point).or_insert(0) += 1;
+        }
+
+        let mut result = per_freq
+            .into_iter()
+            .map(|(freq, points, nodes)| FreqStats {
+                freq,
+                antennas: points.len(),
+                pairs: PairsIter::new(points).count(),
+                antinodes: nodes.len(),
+                shared: nodes.iter().filter(|p| owners[p] > 1).count(),
+            })
+            .collect::<Vec<_>>();
+
+        result.sort_by(|a, b| b.antinodes.cmp(&a.antinodes).then(a.freq.0.cmp(&b.freq.0)));
+        result
+    }
+
+    fn solve_a(&self) -> usize {
+        self.antinodes_a().len()
+    }
+
+    fn solve_b(&self) -> usize {
+        self.antinodes_b().len()
+    }
+
+    /// Map as in the puzzle examples, antennas hide antinodes under them
+    fn render(&self, antinodes: &HashSet<Point>) -> String {
+        let freqs: HashMap<Point, Freq> = self
+            .antennas
+            .iter()
+            .flat_map(|(freq, points)| points.iter().map(|p| (*p, *freq)))
             .collect();
 
-        // dbg!(&total);
-        total.len()
+        let mut result = String::new();
+        for y in 0..self.b_box.height {
+            for x in 0..self.b_box.width {
+                let point = Point { x, y };
+                let c = match freqs.get(&point) {
+                    Some(Freq(f)) => *f,
+                    None if antinodes.contains(&point) => '#',
+                    None => '.',
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+
+        result
     }
 }
 
@@ -226,4 +339,124 @@ fn main() {
 
     let b = game.solve_b();
     println!("B: {}", b);
+
+    if std::env::args().any(|arg| arg == "--render") {
+        print!("{}", game.render(&game.antinodes_b()));
+    }
+
+    if std::env::args().any(|arg| arg == "--report") {
+        println!("Part A:");
+        for stats in game.report_with(Game::freq_antinodes_a) {
+            println!("  {}", stats);
+        }
+
+        println!("Part B:");
+        for stats in game.report_with(Game::freq_antinodes_b) {
+            println!("  {}", stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    const T_EXAMPLE: &str = "T.........
+...T......
+.T........
+..........
+..........
+..........
+..........
+..........
+..........
+..........";
+
+    #[test]
+    fn test_example() {
+        let game = Game::parse(EXAMPLE);
+
+        assert_eq!(game.solve_a(), 14);
+        assert_eq!(game.solve_b(), 34);
+    }
+
+    #[test]
+    fn test_report() {
+        let game = Game::parse(EXAMPLE);
+        let report = game.report_with(Game::freq_antinodes_a);
+
+        assert_eq!(
+            report,
+            [
+                FreqStats {
+                    freq: Freq('0'),
+                    antennas: 4,
+                    pairs: 6,
+                    antinodes: 10,
+                    shared: 1,
+                },
+                FreqStats {
+                    freq: Freq('A'),
+                    antennas: 3,
+                    pairs: 3,
+                    antinodes: 5,
+                    shared: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            report[1].to_string(),
+            "A: 3 antennas, 3 pairs, 5 antinodes, 1 shared"
+        );
+    }
+
+    #[test]
+    fn test_reduced_step() {
+        let game = Game::parse("a....\n.....\n.....\n.....\n..a..");
+        let antinodes = game.antinodes_b();
+
+        assert!(antinodes.contains(&Point::new(1, 2)));
+        assert_eq!(antinodes.len(), 3);
+    }
+
+    #[test]
+    fn test_bbox() {
+        let b_box = BBox::new(10, 5);
+
+        assert!(b_box.within(&Point::new(9, 4)));
+        assert!(!b_box.within(&Point::new(10, 4)));
+        assert!(!b_box.within(&Point::new(9, 5)));
+    }
+
+    #[test]
+    fn test_render() {
+        let game = Game::parse(T_EXAMPLE);
+        let expected = "T....#....
+...T......
+.T....#...
+.........#
+..#.......
+..........
+...#......
+..........
+....#.....
+..........
+";
+
+        assert_eq!(game.render(&game.antinodes_b()), expected);
+        assert_eq!(game.solve_b(), 9);
+    }
 }
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    fmt::Display,
+    time::Instant,
+};
 
 #[derive(Clone, Copy, PartialEq)]
 enum Block {
@@ -10,10 +15,6 @@ impl Block {
     fn is_empty(self) -> bool {
         self == Block::Empty
     }
-
-    fn is_block(self) -> bool {
-        !self.is_empty()
-    }
 }
 
 impl Display for Block {
@@ -149,7 +150,7 @@ impl Disk {
             // println!("Start moving block {id} (pos: {idx}, len {len})");
             // println!("B: {self}");
 
-            if let Some((h_idx, h_len)) = self.holes().find(|(_, hole_len)| hole_len >= len) {
+            if let Some((h_idx, _)) = self.holes().find(|(_, hole_len)| hole_len >= len) {
                 if h_idx < *idx {
                     for i in 0..*len {
                         self.blocks[h_idx + i] = Block::File(id);
@@ -172,14 +173,14 @@ impl Disk {
             .sum()
     }
 
-    fn blocks(&self) -> BlockIter {
+    fn blocks(&self) -> BlockIter<'_> {
         BlockIter {
             current: 0,
             disk: self,
         }
     }
 
-    fn holes(&self) -> HolesIter {
+    fn holes(&self) -> HolesIter<'_> {
         HolesIter {
             current: 0,
             disk: self,
@@ -197,14 +198,165 @@ impl Display for Disk {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+/// Disk kept as spans instead of blocks, files are indexed by id
+#[derive(Debug, Clone)]
+struct SpanDisk {
+    files: Vec<Span>,
+    /// Start of every free span, bucketed by the span length 1..=9
+    free: [BinaryHeap<Reverse<usize>>; 10],
+}
+
+impl SpanDisk {
+    pub fn parse(input: &str) -> Self {
+        let mut files = Vec::new();
+        let mut free: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
+        let mut start = 0;
+
+        for (i, len) in input.chars().map_while(|c| c.to_digit(10)).enumerate() {
+            let len = len as usize;
+            if i % 2 == 0 {
+                files.push(Span { start, len });
+            } else if len > 0 {
+                free[len].push(Reverse(start));
+            }
+            start += len;
+        }
+
+        Self { files, free }
+    }
+
+    /// Move every file, highest id first, to the leftmost free span that
+    /// fits it. The leftmost candidate is the smallest top across the heaps
+    /// for lengths `len..=9`, so each file costs O(log n)
+    pub fn defrag_file(&mut self) {
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id];
+            if file.len == 0 {
+                continue;
+            }
+
+            let best = (file.len..self.free.len())
+                .filter_map(|len| self.free[len].peek().map(|&Reverse(start)| (start, len)))
+                .min();
+
+            let Some((start, len)) = best.filter(|&(start, _)| start < file.start) else {
+                continue;
+            };
+
+            self.free[len].pop();
+            if len > file.len {
+                self.free[len - file.len].push(Reverse(start + file.len));
+            }
+
+            // the freed span is right of every file still to move, never reused
+            self.files[id].start = start;
+        }
+    }
+
+    pub fn checksum(&self) -> usize {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(id, span)| {
+                id * (span.start * span.len + span.len * span.len.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+}
+
 fn main() {
     let input = std::fs::read_to_string("data/day09.txt").unwrap();
     let mut disk_a = Disk::parse(&input);
-    let mut disk_b = disk_a.clone();
-
     disk_a.defrag();
+
+    let mut disk_b = SpanDisk::parse(&input);
     disk_b.defrag_file();
 
     println!("A: {}", disk_a.checksum());
     println!("B: {}", disk_b.checksum());
+
+    if std::env::args().any(|arg| arg == "--bench") {
+        let mut disk = Disk::parse(&input);
+        let now = Instant::now();
+        disk.defrag_file();
+        println!("blocks: {} in {:?}", disk.checksum(), now.elapsed());
+
+        let mut disk = SpanDisk::parse(&input);
+        let now = Instant::now();
+        disk.defrag_file();
+        println!("spans: {} in {:?}", disk.checksum(), now.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    fn blocks_checksum(input: &str) -> usize {
+        let mut disk = Disk::parse(input);
+        disk.defrag_file();
+        disk.checksum()
+    }
+
+    fn spans_checksum(input: &str) -> usize {
+        let mut disk = SpanDisk::parse(input);
+        disk.defrag_file();
+        disk.checksum()
+    }
+
+    #[test]
+    fn test_example() {
+        let mut disk = Disk::parse(EXAMPLE);
+        disk.defrag();
+        assert_eq!(disk.checksum(), 1928);
+
+        assert_eq!(blocks_checksum(EXAMPLE), 2858);
+        assert_eq!(spans_checksum(EXAMPLE), 2858);
+    }
+
+    #[test]
+    fn test_span_parse() {
+        let disk = SpanDisk::parse("12345");
+
+        assert_eq!(
+            disk.files,
+            [
+                Span { start: 0, len: 1 },
+                Span { start: 3, len: 3 },
+                Span { start: 10, len: 5 }
+            ]
+        );
+        assert_eq!(disk.free[2].peek(), Some(&Reverse(1)));
+        assert_eq!(disk.free[4].peek(), Some(&Reverse(6)));
+    }
+
+    #[test]
+    fn test_matches_blocks() {
+        let inputs = [
+            EXAMPLE,
+            "12345",
+            "1",
+            "11",
+            "90909",
+            "10101010101",
+            "19191919191",
+            "2921312",
+            "1919293949596979899",
+            "5030201",
+            "9999999999999999999",
+            "123123123123123123123",
+        ];
+
+        for input in inputs {
+            assert_eq!(spans_checksum(input), blocks_checksum(input), "{input}");
+        }
+    }
 }
@@ -0,0 +1,239 @@
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Robot {
+    px: i64,
+    py: i64,
+    vx: i64,
+    vy: i64,
+}
+
+impl Robot {
+    fn parse(input: &str) -> Self {
+        let numbers = input
+            .split(|c: char| !(c.is_ascii_digit() || c == '-'))
+            .filter(|part| !part.is_empty())
+            .map(|part| part.parse::<i64>().expect("Invalid number"))
+            .collect::<Vec<_>>();
+
+        match numbers[..] {
+            [px, py, vx, vy] => Self { px, py, vx, vy },
+            _ => panic!("Invalid robot: {}", input),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Swarm {
+    width: i64,
+    height: i64,
+    robots: Vec<Robot>,
+}
+
+/// Inverse of `a` modulo `m`, both must be coprime
+fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (mut old_r, mut r) = (a.rem_euclid(m), m);
+    let (mut old_s, mut s) = (1, 0);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+    }
+
+    (old_r == 1).then(|| old_s.rem_euclid(m))
+}
+
+/// Smallest `t` with `t = a (mod m)` and `t = b (mod n)`
+fn crt(a: i64, m: i64, b: i64, n: i64) -> Option<i64> {
+    let inverse = mod_inverse(m, n)?;
+    let k = ((b - a) * inverse).rem_euclid(n);
+    Some(a + m * k)
+}
+
+fn variance(values: impl Iterator<Item = i64>) -> f64 {
+    let values = values.map(|v| v as f64).collect::<Vec<_>>();
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+
+    values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n
+}
+
+impl Swarm {
+    pub fn parse(input: &str, width: i64, height: i64) -> Self {
+        let robots = input.lines().map(Robot::parse).collect();
+        Self {
+            width,
+            height,
+            robots,
+        }
+    }
+
+    fn x_at(&self, robot: &Robot, seconds: i64) -> i64 {
+        (robot.px + robot.vx * seconds).rem_euclid(self.width)
+    }
+
+    fn y_at(&self, robot: &Robot, seconds: i64) -> i64 {
+        (robot.py + robot.vy * seconds).rem_euclid(self.height)
+    }
+
+    pub fn positions(&self, seconds: i64) -> Vec<(i64, i64)> {
+        self.robots
+            .iter()
+            .map(|r| (self.x_at(r, seconds), self.y_at(r, seconds)))
+            .collect()
+    }
+
+    pub fn safety_factor(&self, seconds: i64) -> usize {
+        let mid_x = self.width / 2;
+        let mid_y = self.height / 2;
+        let mut quadrants = [0; 4];
+
+        for (x, y) in self.positions(seconds) {
+            if x == mid_x || y == mid_y {
+                continue;
+            }
+
+            let idx = (x > mid_x) as usize + 2 * (y > mid_y) as usize;
+            quadrants[idx] += 1;
+        }
+
+        quadrants.iter().product()
+    }
+
+    /// The x and y coordinates repeat with periods `width` and `height`, so the
+    /// tree frame is found by minimising the variance on each axis separately
+    /// and joining both with CRT
+    pub fn find_tree(&self) -> Option<i64> {
+        let best = |period: i64, at: &dyn Fn(&Robot, i64) -> i64| {
+            (0..period)
+                .map(|t| (t, variance(self.robots.iter().map(|r| at(r, t)))))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(t, _)| t)
+        };
+
+        let tx = best(self.width, &|r, t| self.x_at(r, t))?;
+        let ty = best(self.height, &|r, t| self.y_at(r, t))?;
+
+        crt(tx, self.width, ty, self.height)
+    }
+
+    /// Frame as a plain PBM image, robots are black pixels
+    pub fn to_pbm(&self, seconds: i64) -> String {
+        let mut grid = vec![vec![false; self.width as usize]; self.height as usize];
+        for (x, y) in self.positions(seconds) {
+            grid[y as usize][x as usize] = true;
+        }
+
+        let mut result = format!("P1\n{} {}\n", self.width, self.height);
+        for row in grid {
+            let line = row
+                .iter()
+                .map(|&b| if b { "1" } else { "0" })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(result, "{}", line).expect("Can't write");
+        }
+
+        result
+    }
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day14.txt").expect("Invalid input data");
+    let swarm = Swarm::parse(&data, 101, 103);
+
+    println!("A: {}", swarm.safety_factor(100));
+
+    let tree = swarm.find_tree().expect("Tree not found");
+    println!("B: {}", tree);
+
+    let args = std::env::args().collect::<Vec<_>>();
+    if let Some(idx) = args.iter().position(|arg| arg == "--pbm") {
+        let path = args.get(idx + 1).map(String::as_str).unwrap_or("day14.pbm");
+        std::fs::write(path, swarm.to_pbm(tree)).expect("Can't write image");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3";
+
+    #[test]
+    fn test_parse() {
+        let robot = Robot::parse("p=0,4 v=3,-3");
+        assert_eq!(
+            robot,
+            Robot {
+                px: 0,
+                py: 4,
+                vx: 3,
+                vy: -3
+            }
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let swarm = Swarm::parse("p=2,4 v=2,-3", 11, 7);
+        assert_eq!(swarm.positions(5), [(1, 3)]);
+    }
+
+    #[test]
+    fn test_safety_factor() {
+        let swarm = Swarm::parse(EXAMPLE, 11, 7);
+        assert_eq!(swarm.safety_factor(100), 12);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(mod_inverse(101, 103), Some(51));
+        assert_eq!(crt(2, 101, 5, 103), Some(5052));
+        assert_eq!(5052 % 101, 2);
+        assert_eq!(5052 % 103, 5);
+    }
+
+    #[test]
+    fn test_find_tree() {
+        // robots gathered in a small square at a known second
+        let target = 1234;
+        let robots = (0..100)
+            .map(|i: i64| {
+                let (vx, vy) = ((i * 37) % 201 - 100, (i * 53) % 199 - 99);
+                Robot {
+                    px: (50 + i % 5 - vx * target).rem_euclid(101),
+                    py: (50 + i / 20 - vy * target).rem_euclid(103),
+                    vx,
+                    vy,
+                }
+            })
+            .collect();
+
+        let swarm = Swarm {
+            width: 101,
+            height: 103,
+            robots,
+        };
+
+        assert_eq!(swarm.find_tree(), Some(target));
+    }
+
+    #[test]
+    fn test_to_pbm() {
+        let swarm = Swarm::parse("p=1,0 v=0,0", 3, 2);
+        assert_eq!(swarm.to_pbm(0), "P1\n3 2\n0 1 0\n0 0 0\n");
+    }
+}
@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+impl Position {
+    fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    fn to(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self::new(self.x, self.y - 1),
+            Direction::Right => Self::new(self.x + 1, self.y),
+            Direction::Down => Self::new(self.x, self.y + 1),
+            Direction::Left => Self::new(self.x - 1, self.y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    fn parse(input: char) -> Option<Self> {
+        match input {
+            '^' => Some(Self::Up),
+            '>' => Some(Self::Right),
+            'v' => Some(Self::Down),
+            '<' => Some(Self::Left),
+            _ => None,
+        }
+    }
+
+    fn is_vertical(&self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Empty,
+    Wall,
+    Box,
+    BoxLeft,
+    BoxRight,
+}
+
+impl Tile {
+    fn to_char(self) -> char {
+        match self {
+            Self::Empty => '.',
+            Self::Wall => '#',
+            Self::Box => 'O',
+            Self::BoxLeft => '[',
+            Self::BoxRight => ']',
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Warehouse {
+    width: i32,
+    height: i32,
+    tiles: Vec<Tile>,
+    initial_tiles: Vec<Tile>,
+    robot: Position,
+    initial_robot: Position,
+    moves: Vec<Direction>,
+    tick: usize,
+}
+
+impl Warehouse {
+    /// Parse the map, with `wide` every tile is doubled as in part B
+    pub fn parse(input: &str, wide: bool) -> Self {
+        let (map, moves) = input.split_once("\n\n").expect("Invalid input");
+
+        let mut width = 0;
+        let mut height = 0;
+        let mut tiles = vec![];
+        let mut robot = None;
+
+        for (y, line) in map.lines().enumerate() {
+            height += 1;
+            let mut x = 0;
+
+            for c in line.chars() {
+                let parsed: &[Tile] = match (c, wide) {
+                    ('#', false) => &[Tile::Wall],
+                    ('#', true) => &[Tile::Wall, Tile::Wall],
+                    ('O', false) => &[Tile::Box],
+                    ('O', true) => &[Tile::BoxLeft, Tile::BoxRight],
+                    ('@', _) => {
+                        robot = Some(Position::new(x, y as i32));
+                        if wide {
+                            &[Tile::Empty, Tile::Empty]
+                        } else {
+                            &[Tile::Empty]
+                        }
+                    }
+                    (_, false) => &[Tile::Empty],
+                    (_, true) => &[Tile::Empty, Tile::Empty],
+                };
+
+                tiles.extend_from_slice(parsed);
+                x += parsed.len() as i32;
+            }
+
+            width = width.max(x);
+        }
+
+        let moves = moves.chars().filter_map(Direction::parse).collect();
+        let robot = robot.expect("Robot not found in map");
+
+        Self {
+            width,
+            height,
+            initial_tiles: tiles.clone(),
+            tiles,
+            robot,
+            initial_robot: robot,
+            moves,
+            tick: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.tiles = self.initial_tiles.clone();
+        self.robot = self.initial_robot;
+        self.tick = 0;
+    }
+
+    fn at(&self, position: Position) -> Tile {
+        if position.x < 0 || position.y < 0 || position.x >= self.width || position.y >= self.height
+        {
+            Tile::Wall
+        } else {
+            self.tiles[(position.y * self.width + position.x) as usize]
+        }
+    }
+
+    fn set(&mut self, position: Position, tile: Tile) {
+        self.tiles[(position.y * self.width + position.x) as usize] = tile;
+    }
+
+    /// Collect every box cell pushed when something moves into `position`,
+    /// returns false if any of them hits a wall
+    fn collect_push(
+        &self,
+        position: Position,
+        direction: Direction,
+        pushed: &mut Vec<Position>,
+        seen: &mut HashSet<Position>,
+    ) -> bool {
+        if seen.contains(&position) {
+            return true;
+        }
+
+        let mut cells = match self.at(position) {
+            Tile::Empty => return true,
+            Tile::Wall => return false,
+            Tile::Box => vec![position],
+            Tile::BoxLeft => vec![position, position.to(Direction::Right)],
+            Tile::BoxRight => vec![position.to(Direction::Left), position],
+        };
+
+        // a wide box pushed sideways only moves as a chain, no need to split it
+        if !direction.is_vertical() {
+            cells.retain(|&p| p == position);
+        }
+
+        for cell in cells {
+            if seen.insert(cell) {
+                pushed.push(cell);
+                if !self.collect_push(cell.to(direction), direction, pushed, seen) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Apply the next move, returns false once all moves are done
+    pub fn tick(&mut self) -> bool {
+        let Some(&direction) = self.moves.get(self.tick) else {
+            return false;
+        };
+        self.tick += 1;
+
+        let next = self.robot.to(direction);
+        let mut pushed = vec![];
+        let mut seen = HashSet::new();
+
+        if self.collect_push(next, direction, &mut pushed, &mut seen) {
+            let moved = pushed
+                .iter()
+                .map(|&p| (p.to(direction), self.at(p)))
+                .collect::<Vec<_>>();
+
+            for &p in pushed.iter() {
+                self.set(p, Tile::Empty);
+            }
+
+            for (p, tile) in moved {
+                self.set(p, tile);
+            }
+
+            self.robot = next;
+        }
+
+        true
+    }
+
+    pub fn run(&mut self) {
+        while self.tick() {}
+    }
+
+    pub fn gps_sum(&self) -> i32 {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| Position::new(x, y)))
+            .filter(|&p| matches!(self.at(p), Tile::Box | Tile::BoxLeft))
+            .map(|p| p.y * 100 + p.x)
+            .sum()
+    }
+
+    pub fn render(&self) -> String {
+        let mut result = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let position = Position::new(x, y);
+                if position == self.robot {
+                    result.push('@');
+                } else {
+                    result.push(self.at(position).to_char());
+                }
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day15.txt").expect("Invalid input data");
+    let frames = std::env::args().any(|arg| arg == "--frames");
+
+    let mut warehouse_a = Warehouse::parse(&data, false);
+    let mut warehouse_b = Warehouse::parse(&data, true);
+
+    if frames {
+        print!("{}", warehouse_b.render());
+        while warehouse_b.tick() {
+            println!();
+            print!("{}", warehouse_b.render());
+        }
+        warehouse_b.reset();
+    }
+
+    warehouse_a.run();
+    warehouse_b.run();
+
+    println!("A: {}", warehouse_a.gps_sum());
+    println!("B: {}", warehouse_b.gps_sum());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SMALL: &str = "########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+
+    const LARGE: &str = "##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
+
+    #[test]
+    fn test_small() {
+        let mut warehouse = Warehouse::parse(SMALL, false);
+        warehouse.run();
+
+        assert_eq!(warehouse.gps_sum(), 2028);
+    }
+
+    #[test]
+    fn test_large() {
+        let mut warehouse = Warehouse::parse(LARGE, false);
+        warehouse.run();
+        assert_eq!(warehouse.gps_sum(), 10092);
+
+        let mut warehouse = Warehouse::parse(LARGE, true);
+        warehouse.run();
+        assert_eq!(warehouse.gps_sum(), 9021);
+    }
+
+    #[test]
+    fn test_wide_push() {
+        let input = "#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<vv<<^^<<^^";
+
+        let mut warehouse = Warehouse::parse(input, true);
+        warehouse.run();
+
+        let expected = "##############
+##...[].##..##
+##...@.[]...##
+##....[]....##
+##..........##
+##..........##
+##############
+";
+        assert_eq!(warehouse.render(), expected);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut warehouse = Warehouse::parse(SMALL, false);
+        let initial = warehouse.render();
+
+        warehouse.run();
+        assert_ne!(warehouse.render(), initial);
+
+        warehouse.reset();
+        assert_eq!(warehouse.render(), initial);
+    }
+}
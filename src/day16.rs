@@ -0,0 +1,379 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+const MOVE_COST: usize = 1;
+const TURN_COST: usize = 1000;
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, PartialOrd, Ord)]
+struct Position {
+    x: i32,
+    y: i32,
+}
+
+impl Position {
+    fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    fn from_usize(x: usize, y: usize) -> Self {
+        Self {
+            x: x as i32,
+            y: y as i32,
+        }
+    }
+
+    fn to(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::Up => Self::new(self.x, self.y - 1),
+            Direction::Right => Self::new(self.x + 1, self.y),
+            Direction::Down => Self::new(self.x, self.y + 1),
+            Direction::Left => Self::new(self.x - 1, self.y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn rotate(&self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn rotate_back(&self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Right => Self::Up,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+        }
+    }
+
+    fn opposite(&self) -> Self {
+        self.rotate().rotate()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Reindeer {
+    position: Position,
+    direction: Direction,
+}
+
+impl Reindeer {
+    /// Next states with the cost of getting there
+    fn moves(&self) -> [(Reindeer, usize); 3] {
+        [
+            (
+                Reindeer {
+                    position: self.position.to(self.direction),
+                    direction: self.direction,
+                },
+                MOVE_COST,
+            ),
+            (
+                Reindeer {
+                    position: self.position,
+                    direction: self.direction.rotate(),
+                },
+                TURN_COST,
+            ),
+            (
+                Reindeer {
+                    position: self.position,
+                    direction: self.direction.rotate_back(),
+                },
+                TURN_COST,
+            ),
+        ]
+    }
+
+    /// Previous states with the cost of getting here from them
+    fn unmoves(&self) -> [(Reindeer, usize); 3] {
+        [
+            (
+                Reindeer {
+                    position: self.position.to(self.direction.opposite()),
+                    direction: self.direction,
+                },
+                MOVE_COST,
+            ),
+            (
+                Reindeer {
+                    position: self.position,
+                    direction: self.direction.rotate(),
+                },
+                TURN_COST,
+            ),
+            (
+                Reindeer {
+                    position: self.position,
+                    direction: self.direction.rotate_back(),
+                },
+                TURN_COST,
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+struct Maze {
+    width: i32,
+    height: i32,
+    walls: HashSet<Position>,
+    start: Position,
+    end: Position,
+}
+
+#[derive(Debug)]
+struct Solution {
+    score: usize,
+    tiles: HashSet<Position>,
+}
+
+impl Maze {
+    pub fn parse(input: &str) -> Self {
+        let mut width = 0;
+        let mut height = 0;
+        let mut walls = HashSet::new();
+        let mut start = None;
+        let mut end = None;
+
+        for (y, line) in input.lines().enumerate() {
+            height += 1;
+
+            for (x, c) in line.chars().enumerate() {
+                width = (x as i32 + 1).max(width);
+                let point = Position::from_usize(x, y);
+
+                match c {
+                    '#' => {
+                        walls.insert(point);
+                    }
+                    'S' => start = Some(point),
+                    'E' => end = Some(point),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            walls,
+            start: start.expect("Start not found in map"),
+            end: end.expect("End not found in map"),
+        }
+    }
+
+    /// Anything outside of the map is a wall too
+    fn is_wall(&self, position: Position) -> bool {
+        position.x < 0
+            || position.y < 0
+            || position.x >= self.width
+            || position.y >= self.height
+            || self.walls.contains(&position)
+    }
+
+    fn distances(&self) -> HashMap<Reindeer, usize> {
+        let start = Reindeer {
+            position: self.start,
+            direction: Direction::Right,
+        };
+
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+
+        while let Some(Reverse((cost, current))) = queue.pop() {
+            if distances.get(&current).is_some_and(|&d| d < cost) {
+                continue;
+            }
+
+            for (next, step) in current.moves() {
+                if self.is_wall(next.position) {
+                    continue;
+                }
+
+                let next_cost = cost + step;
+                if distances.get(&next).is_none_or(|&d| next_cost < d) {
+                    distances.insert(next, next_cost);
+                    queue.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        distances
+    }
+
+    pub fn solve(&self) -> Option<Solution> {
+        let distances = self.distances();
+
+        let ends = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ]
+        .map(|direction| Reindeer {
+            position: self.end,
+            direction,
+        });
+
+        let score = ends
+            .iter()
+            .filter_map(|e| distances.get(e))
+            .min()
+            .copied()?;
+
+        // walk the predecessor DAG back from every optimal end state
+        let mut visited = HashSet::new();
+        let mut queue = ends
+            .into_iter()
+            .filter(|e| distances.get(e) == Some(&score))
+            .collect::<Vec<_>>();
+
+        while let Some(current) = queue.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+
+            let cost = distances[&current];
+            for (prev, step) in current.unmoves() {
+                if distances.get(&prev).is_some_and(|&d| d + step == cost) {
+                    queue.push(prev);
+                }
+            }
+        }
+
+        let tiles = visited.into_iter().map(|r| r.position).collect();
+
+        Some(Solution { score, tiles })
+    }
+
+    /// Map with every tile of any best path marked with `O`
+    pub fn render(&self, tiles: &HashSet<Position>) -> String {
+        let mut result = String::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let point = Position::new(x, y);
+                let c = if self.walls.contains(&point) {
+                    '#'
+                } else if tiles.contains(&point) {
+                    'O'
+                } else {
+                    '.'
+                };
+                result.push(c);
+            }
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day16.txt").expect("Invalid input data");
+    let maze = Maze::parse(&data);
+    let solution = maze.solve().expect("No path found");
+
+    if std::env::args().any(|arg| arg == "--render") {
+        print!("{}", maze.render(&solution.tiles));
+    }
+
+    println!("A: {}", solution.score);
+    println!("B: {}", solution.tiles.len());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE_1: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    const EXAMPLE_2: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+    #[test]
+    fn test_direction_rotate() {
+        let direction = Direction::Left;
+        assert_eq!(direction.rotate().rotate_back(), direction);
+        assert_eq!(direction.opposite(), Direction::Right);
+    }
+
+    #[test]
+    fn test_example_1() {
+        let solution = Maze::parse(EXAMPLE_1).solve().unwrap();
+
+        assert_eq!(solution.score, 7036);
+        assert_eq!(solution.tiles.len(), 45);
+    }
+
+    #[test]
+    fn test_example_2() {
+        let solution = Maze::parse(EXAMPLE_2).solve().unwrap();
+
+        assert_eq!(solution.score, 11048);
+        assert_eq!(solution.tiles.len(), 64);
+    }
+
+    #[test]
+    fn test_no_border() {
+        let solution = Maze::parse("S.E").solve().unwrap();
+
+        assert_eq!(solution.score, 2);
+        assert_eq!(solution.tiles.len(), 3);
+    }
+
+    #[test]
+    fn test_render() {
+        let maze = Maze::parse(EXAMPLE_2);
+        let solution = maze.solve().unwrap();
+        let render = maze.render(&solution.tiles);
+
+        assert!(render.starts_with("#################\n#...#...#...#..O#\n"));
+        assert_eq!(render.matches('O').count(), 64);
+    }
+}
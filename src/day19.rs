@@ -1,4 +1,8 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    time::Instant,
+};
 
 fn is_possible_partial<'a>(
     collection: &Collection,
@@ -48,6 +52,15 @@ struct Pattern(Vec<char>);
 #[derive(Debug)]
 struct Collection(HashSet<Pattern>);
 
+#[derive(Debug, PartialEq)]
+enum Explanation<'a> {
+    Possible(Vec<&'a Pattern>),
+    Impossible {
+        prefix: Vec<&'a Pattern>,
+        failed_at: usize,
+    },
+}
+
 #[derive(Debug)]
 struct Towel(Vec<char>);
 
@@ -74,6 +87,16 @@ impl Pattern {
     }
 }
 
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in &self.0 {
+            write!(f, "{c}")?
+        }
+
+        Ok(())
+    }
+}
+
 impl Collection {
     fn parse(input: &str) -> Self {
         let patterns = input.split(", ").map(Pattern::parse).collect();
@@ -84,6 +107,172 @@ impl Collection {
         let mut cache = Cache::new();
         is_possible_partial(self, &towel.0, &mut cache)
     }
+
+    fn arrangements(&self, towel: &Towel) -> u64 {
+        let len = towel.0.len();
+
+        // counts[pos] is the number of ways to build the suffix starting at pos
+        let mut counts = vec![0u64; len + 1];
+        counts[len] = 1;
+
+        for pos in (0..len).rev() {
+            counts[pos] = self
+                .0
+                .iter()
+                .filter(|pattern| pattern.is_match(&towel.0[pos..]).is_some())
+                .map(|pattern| counts[pos + pattern.0.len()])
+                .sum();
+        }
+
+        counts[0]
+    }
+
+    /// Returns one decomposition of the towel, or the longest buildable prefix
+    /// and the position where no pattern matches anymore
+    fn explain(&self, towel: &Towel) -> Explanation<'_> {
+        let len = towel.0.len();
+
+        // prev[pos] is the position and pattern used to reach pos first
+        let mut prev: Vec<Option<(usize, &Pattern)>> = vec![None; len + 1];
+        let mut farthest = 0;
+
+        for pos in 0..len {
+            if pos > 0 && prev[pos].is_none() {
+                continue;
+            }
+
+            for pattern in self.0.iter() {
+                if pattern.is_match(&towel.0[pos..]).is_some() {
+                    let next = pos + pattern.0.len();
+                    if prev[next].is_none() {
+                        prev[next] = Some((pos, pattern));
+                        farthest = farthest.max(next);
+                    }
+                }
+            }
+        }
+
+        let mut prefix = vec![];
+        let mut pos = farthest;
+        while let Some((from, pattern)) = prev[pos] {
+            prefix.push(pattern);
+            pos = from;
+        }
+        prefix.reverse();
+
+        if farthest == len {
+            Explanation::Possible(prefix)
+        } else {
+            Explanation::Impossible {
+                prefix,
+                failed_at: farthest,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    terminal: bool,
+}
+
+#[derive(Debug)]
+struct Trie(Vec<TrieNode>);
+
+struct TrieMatches<'a> {
+    trie: &'a Trie,
+    input: &'a [char],
+    node: Option<usize>,
+    pos: usize,
+}
+
+impl Trie {
+    fn new(collection: &Collection) -> Self {
+        let mut nodes = vec![TrieNode::default()];
+
+        for pattern in collection.0.iter() {
+            let mut node = 0;
+            for c in pattern.0.iter() {
+                node = match nodes[node].children.get(c) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(TrieNode::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].children.insert(*c, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].terminal = true;
+        }
+
+        Self(nodes)
+    }
+
+    /// Lengths of all patterns that are a prefix of `input`, shortest first
+    fn matches<'a>(&'a self, input: &'a [char]) -> TrieMatches<'a> {
+        TrieMatches {
+            trie: self,
+            input,
+            node: Some(0),
+            pos: 0,
+        }
+    }
+
+    fn is_possible(&self, towel: &Towel) -> bool {
+        let len = towel.0.len();
+        let mut possible = vec![false; len + 1];
+        possible[len] = true;
+
+        for pos in (0..len).rev() {
+            possible[pos] = self
+                .matches(&towel.0[pos..])
+                .any(|matched| possible[pos + matched]);
+        }
+
+        possible[0]
+    }
+
+    fn arrangements(&self, towel: &Towel) -> u64 {
+        let len = towel.0.len();
+        let mut counts = vec![0u64; len + 1];
+        counts[len] = 1;
+
+        for pos in (0..len).rev() {
+            counts[pos] = self
+                .matches(&towel.0[pos..])
+                .map(|matched| counts[pos + matched])
+                .sum();
+        }
+
+        counts[0]
+    }
+}
+
+impl Iterator for TrieMatches<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.node {
+            if self.pos >= self.input.len() {
+                self.node = None;
+                break;
+            }
+
+            let c = self.input[self.pos];
+            self.node = self.trie.0[node].children.get(&c).copied();
+            self.pos += 1;
+
+            if let Some(next) = self.node {
+                if self.trie.0[next].terminal {
+                    return Some(self.pos);
+                }
+            }
+        }
+
+        None
+    }
 }
 
 impl Towel {
@@ -93,9 +282,40 @@ impl Towel {
     }
 }
 
-fn main() {
-    let data = std::fs::read_to_string("data/day19.txt").expect("Invalid file");
-    let mut parts = data.split("\n\n");
+impl Display for Towel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for c in &self.0 {
+            write!(f, "{c}")?
+        }
+
+        Ok(())
+    }
+}
+
+fn join(patterns: &[&Pattern]) -> String {
+    patterns
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn explain(patterns: &Collection, towels: &[Towel]) {
+    for towel in towels {
+        match patterns.explain(towel) {
+            Explanation::Possible(parts) => println!("{towel}: {}", join(&parts)),
+            Explanation::Impossible { prefix, failed_at } => {
+                println!(
+                    "{towel}: impossible, built [{}], failed at {failed_at}",
+                    join(&prefix)
+                )
+            }
+        }
+    }
+}
+
+fn parse(input: &str) -> (Collection, Vec<Towel>) {
+    let mut parts = input.split("\n\n");
     let patterns = Collection::parse(parts.next().expect("invalid input"));
     let towels = parts
         .next()
@@ -104,6 +324,135 @@ fn main() {
         .map(Towel::parse)
         .collect::<Vec<_>>();
 
-    let total = towels.iter().filter(|t| patterns.is_possible(t)).count();
-    dbg!(total);
+    (patterns, towels)
+}
+
+fn bench(patterns: &Collection, towels: &[Towel]) {
+    let now = Instant::now();
+    let linear_a = towels.iter().filter(|t| patterns.is_possible(t)).count();
+    let linear_b: u64 = towels.iter().map(|t| patterns.arrangements(t)).sum();
+    let linear = now.elapsed();
+
+    let now = Instant::now();
+    let trie = Trie::new(patterns);
+    let trie_a = towels.iter().filter(|t| trie.is_possible(t)).count();
+    let trie_b: u64 = towels.iter().map(|t| trie.arrangements(t)).sum();
+    let trie_elapsed = now.elapsed();
+
+    assert_eq!(linear_a, trie_a);
+    assert_eq!(linear_b, trie_b);
+
+    println!("linear: {:?}", linear);
+    println!("trie:   {:?}", trie_elapsed);
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day19.txt").expect("Invalid file");
+    let (patterns, towels) = parse(&data);
+
+    if std::env::args().any(|arg| arg == "--bench") {
+        bench(&patterns, &towels);
+        return;
+    }
+
+    if std::env::args().any(|arg| arg == "--explain") {
+        explain(&patterns, &towels);
+        return;
+    }
+
+    let trie = Trie::new(&patterns);
+    let total_a = towels.iter().filter(|t| trie.is_possible(t)).count();
+    let total_b: u64 = towels.iter().map(|t| trie.arrangements(t)).sum();
+
+    println!("A: {}", total_a);
+    println!("B: {}", total_b);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb";
+
+    #[test]
+    fn test_is_possible() {
+        let (patterns, towels) = parse(EXAMPLE);
+        let total = towels.iter().filter(|t| patterns.is_possible(t)).count();
+        assert_eq!(total, 6);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let (patterns, towels) = parse(EXAMPLE);
+        let counts = towels
+            .iter()
+            .map(|t| patterns.arrangements(t))
+            .collect::<Vec<_>>();
+
+        assert_eq!(counts, [2, 1, 4, 6, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_explain() {
+        let (patterns, towels) = parse(EXAMPLE);
+
+        for towel in towels.iter() {
+            match patterns.explain(towel) {
+                Explanation::Possible(parts) => {
+                    let built = parts.iter().flat_map(|p| p.0.iter()).copied();
+                    assert!(built.eq(towel.0.iter().copied()));
+                }
+                Explanation::Impossible { prefix, failed_at } => {
+                    let len: usize = prefix.iter().map(|p| p.0.len()).sum();
+                    assert_eq!(len, failed_at);
+                    assert!(!patterns.is_possible(towel));
+                }
+            }
+        }
+
+        let ubwu = patterns.explain(&Towel::parse("ubwu"));
+        assert_eq!(
+            ubwu,
+            Explanation::Impossible {
+                prefix: vec![],
+                failed_at: 0
+            }
+        );
+
+        let bbrgwb = patterns.explain(&Towel::parse("bbrgwb"));
+        assert!(matches!(
+            bbrgwb,
+            Explanation::Impossible { failed_at: 4, .. }
+        ));
+    }
+
+    #[test]
+    fn test_trie_matches() {
+        let (patterns, _) = parse(EXAMPLE);
+        let trie = Trie::new(&patterns);
+        let input = "bwurrg".chars().collect::<Vec<_>>();
+
+        assert_eq!(trie.matches(&input).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(trie.matches(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_trie_agrees_with_linear() {
+        let (patterns, towels) = parse(EXAMPLE);
+        let trie = Trie::new(&patterns);
+
+        for towel in towels.iter() {
+            assert_eq!(trie.is_possible(towel), patterns.is_possible(towel));
+            assert_eq!(trie.arrangements(towel), patterns.arrangements(towel));
+        }
+    }
 }
@@ -0,0 +1,177 @@
+fn around(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    [(x + 1, y), (x, y + 1), (x - 1, y), (x, y - 1)].into_iter()
+}
+
+#[derive(Debug)]
+struct Race {
+    width: i32,
+    height: i32,
+    walls: Vec<bool>,
+    start: (i32, i32),
+    end: (i32, i32),
+}
+
+impl Race {
+    pub fn parse(input: &str) -> Self {
+        let mut width = 0;
+        let mut height = 0;
+        let mut walls = vec![];
+        let mut start = None;
+        let mut end = None;
+
+        for (y, line) in input.lines().enumerate() {
+            height += 1;
+
+            for (x, c) in line.chars().enumerate() {
+                width = width.max(x as i32 + 1);
+                match c {
+                    'S' => start = Some((x as i32, y as i32)),
+                    'E' => end = Some((x as i32, y as i32)),
+                    _ => {}
+                }
+
+                walls.push(c == '#');
+            }
+        }
+
+        let start = start.expect("Start not found");
+        let end = end.expect("End not found");
+
+        Self {
+            width,
+            height,
+            walls,
+            start,
+            end,
+        }
+    }
+
+    fn idx(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    fn is_wall(&self, x: i32, y: i32) -> bool {
+        self.idx(x, y).map(|idx| self.walls[idx]).unwrap_or(true)
+    }
+
+    /// Distance from the start for every cell of the single track
+    pub fn distances(&self) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.walls.len()];
+        let mut current = self.start;
+        let mut distance = 0;
+
+        loop {
+            let idx = self.idx(current.0, current.1).expect("Track left the map");
+            distances[idx] = Some(distance);
+
+            if current == self.end {
+                break;
+            }
+
+            current = around(current.0, current.1)
+                .find(|&(x, y)| {
+                    !self.is_wall(x, y)
+                        && self
+                            .idx(x, y)
+                            .map(|idx| distances[idx].is_none())
+                            .unwrap_or(false)
+                })
+                .expect("Track has a dead end");
+            distance += 1;
+        }
+
+        distances
+    }
+
+    /// Count cheats up to `max_len` picoseconds saving at least `min_saving`
+    pub fn cheats(&self, max_len: i32, min_saving: usize) -> usize {
+        let distances = self.distances();
+        let mut total = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let Some(from) = self.idx(x, y).and_then(|idx| distances[idx]) else {
+                    continue;
+                };
+
+                for dy in -max_len..=max_len {
+                    let rest = max_len - dy.abs();
+                    for dx in -rest..=rest {
+                        let Some(to) = self.idx(x + dx, y + dy).and_then(|idx| distances[idx])
+                        else {
+                            continue;
+                        };
+
+                        let cheat_len = (dx.abs() + dy.abs()) as usize;
+                        if to >= from + cheat_len + min_saving {
+                            total += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        total
+    }
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day20.txt").expect("Invalid input data");
+    let race = Race::parse(&data);
+
+    println!("A: {}", race.cheats(2, 100));
+    println!("B: {}", race.cheats(20, 100));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+    #[test]
+    fn test_distances() {
+        let race = Race::parse(EXAMPLE);
+        let distances = race.distances();
+        let end = race.idx(race.end.0, race.end.1).unwrap();
+
+        assert_eq!(distances[end], Some(84));
+    }
+
+    #[test]
+    fn test_cheats_short() {
+        let race = Race::parse(EXAMPLE);
+
+        assert_eq!(race.cheats(2, 64), 1);
+        assert_eq!(race.cheats(2, 40), 2);
+        assert_eq!(race.cheats(2, 20), 5);
+        assert_eq!(race.cheats(2, 2), 44);
+    }
+
+    #[test]
+    fn test_cheats_long() {
+        let race = Race::parse(EXAMPLE);
+
+        assert_eq!(race.cheats(20, 76), 3);
+        assert_eq!(race.cheats(20, 74), 7);
+        assert_eq!(race.cheats(20, 50), 285);
+    }
+}
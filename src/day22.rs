@@ -16,6 +16,7 @@ fn step(input: i64) -> i64 {
     input % 16777216
 }
 
+#[cfg(test)]
 fn step_n(input: i64, n: usize) -> i64 {
     let mut value = input;
     for _ in 0..n {
@@ -52,7 +53,8 @@ struct PriceItem {
 #[derive(Debug)]
 struct SecretNumber {
     last_num: i64,
-    changes: HashMap<Seq, i8>,
+    prices: Vec<i8>,
+    changes: HashMap<Seq, usize>,
 }
 
 impl SecretNumber {
@@ -76,26 +78,36 @@ impl SecretNumber {
         }
 
         let last_num = numbers[N_COUNT - 1].num;
+        let prices = numbers.iter().map(|n| n.price).collect();
+
+        // The first item has no previous price, so windows start at index 1.
+        // The monkey sells at the first occurrence of a sequence, so only the
+        // first index of each one is kept.
+        let mut changes = HashMap::new();
+        for idx in 1..N_COUNT - 3 {
+            let seq = Seq::new(
+                numbers[idx].change,
+                numbers[idx + 1].change,
+                numbers[idx + 2].change,
+                numbers[idx + 3].change,
+            );
+
+            changes.entry(seq).or_insert(idx + 3);
+        }
+
+        Self {
+            last_num,
+            prices,
+            changes,
+        }
+    }
 
-        let changes = (1..N_COUNT - 3)
-            .map(|idx| {
-                let seq = Seq::new(
-                    numbers[idx].change,
-                    numbers[idx + 1].change,
-                    numbers[idx + 2].change,
-                    numbers[idx + 3].change,
-                );
-
-                let price = numbers[idx + 3].price;
-                (seq, price)
-            })
-            .collect::<HashMap<_, _>>();
-
-        Self { last_num, changes }
+    fn prices(&self) -> &[i8] {
+        &self.prices
     }
 
     fn find_price(&self, seq: &Seq) -> Option<i8> {
-        self.changes.get(seq).copied()
+        self.changes.get(seq).map(|&idx| self.prices()[idx])
     }
 }
 
@@ -118,11 +130,7 @@ impl Buyers {
 
     fn task_b(&self) -> i64 {
         seq_generator()
-            .enumerate()
-            .map(|(idx, seq)| {
-                // println!("{}", idx);
-                self.price_at(&seq)
-            })
+            .map(|seq| self.price_at(&seq))
             .max()
             .unwrap_or_default()
     }
@@ -141,6 +149,31 @@ fn main() {
     let buyers = Buyers::parse(&data);
     println!("A: {}", buyers.task_a());
     println!("B: {}", buyers.task_b());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
 
-    // let seq = seq_generator();
+    #[test]
+    fn test_step_n() {
+        assert_eq!(step_n(123, 1), 15887950);
+        assert_eq!(step_n(1, 2000), 8685429);
+    }
+
+    #[test]
+    fn test_prices() {
+        let number = SecretNumber::new(123);
+        assert_eq!(number.prices()[0..4], [3, 0, 6, 5]);
+        assert_eq!(number.prices().len(), N_COUNT);
+    }
+
+    #[test]
+    fn test_first_occurrence() {
+        let buyers = Buyers::parse("1\n2\n3\n2024");
+        let seq = Seq::new(-2, 1, -1, 3);
+
+        assert_eq!(buyers.price_at(&seq), 23);
+        assert_eq!(buyers.0[2].find_price(&seq), None);
+    }
 }
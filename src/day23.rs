@@ -0,0 +1,377 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug)]
+struct Network {
+    names: Vec<String>,
+    links: Vec<HashSet<usize>>,
+}
+
+impl Network {
+    pub fn parse(input: &str) -> Self {
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut names = vec![];
+        let mut links: Vec<HashSet<usize>> = vec![];
+
+        for line in input.lines() {
+            let mut parts = line.split('-');
+            let a = parts.next().expect("Invalid input");
+            let b = parts.next().expect("Invalid input");
+
+            let [a, b] = [a, b].map(|name| {
+                *ids.entry(name).or_insert_with(|| {
+                    names.push(name.to_string());
+                    links.push(HashSet::new());
+                    names.len() - 1
+                })
+            });
+
+            links[a].insert(b);
+            links[b].insert(a);
+        }
+
+        Self { names, links }
+    }
+
+    pub fn triangles_with_t(&self) -> usize {
+        let mut total = 0;
+
+        for a in 0..self.names.len() {
+            for &b in self.links[a].iter().filter(|&&b| b > a) {
+                for &c in self.links[b].iter().filter(|&&c| c > b) {
+                    if self.links[a].contains(&c)
+                        && [a, b, c].iter().any(|&n| self.names[n].starts_with('t'))
+                    {
+                        total += 1;
+                    }
+                }
+            }
+        }
+
+        total
+    }
+
+    pub fn max_clique(&self) -> Vec<usize> {
+        let mut best = vec![];
+        let all = (0..self.names.len()).collect();
+
+        self.bron_kerbosch(vec![], all, HashSet::new(), &mut |clique| {
+            if clique.len() > best.len() {
+                best = clique.to_vec();
+            }
+        });
+
+        best
+    }
+
+    /// Bron–Kerbosch with pivoting, calls `found` for every maximal clique
+    fn bron_kerbosch(
+        &self,
+        clique: Vec<usize>,
+        mut candidates: HashSet<usize>,
+        mut excluded: HashSet<usize>,
+        found: &mut impl FnMut(&[usize]),
+    ) {
+        if candidates.is_empty() && excluded.is_empty() {
+            found(&clique);
+            return;
+        }
+
+        let pivot = candidates
+            .union(&excluded)
+            .max_by_key(|&&n| self.links[n].len())
+            .copied()
+            .expect("Can't be empty");
+
+        let rest = candidates
+            .difference(&self.links[pivot])
+            .copied()
+            .collect::<Vec<_>>();
+
+        for node in rest {
+            let mut next = clique.clone();
+            next.push(node);
+
+            let links = &self.links[node];
+            self.bron_kerbosch(
+                next,
+                candidates.intersection(links).copied().collect(),
+                excluded.intersection(links).copied().collect(),
+                found,
+            );
+
+            candidates.remove(&node);
+            excluded.insert(node);
+        }
+    }
+
+    pub fn maximal_cliques(&self) -> Vec<Vec<usize>> {
+        let mut cliques = vec![];
+        let all = (0..self.names.len()).collect();
+
+        self.bron_kerbosch(vec![], all, HashSet::new(), &mut |clique| {
+            cliques.push(clique.to_vec());
+        });
+
+        cliques
+    }
+
+    /// Number of nodes for every degree
+    pub fn degrees(&self) -> BTreeMap<usize, usize> {
+        let mut result = BTreeMap::new();
+        for links in self.links.iter() {
+            *result.entry(links.len()).or_insert(0) += 1;
+        }
+
+        result
+    }
+
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut visited = vec![false; self.names.len()];
+        let mut components = vec![];
+
+        for start in 0..self.names.len() {
+            if visited[start] {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut component = vec![];
+            let mut queue = vec![start];
+
+            while let Some(node) = queue.pop() {
+                component.push(node);
+                for &next in self.links[node].iter() {
+                    if !visited[next] {
+                        visited[next] = true;
+                        queue.push(next);
+                    }
+                }
+            }
+
+            component.sort();
+            components.push(component);
+        }
+
+        components
+    }
+
+    /// Core number of every node: the largest k such that the node belongs
+    /// to a subgraph where all nodes have at least k neighbours
+    pub fn core_numbers(&self) -> Vec<usize> {
+        let mut degrees = self.links.iter().map(|l| l.len()).collect::<Vec<_>>();
+        let mut removed = vec![false; self.names.len()];
+        let mut cores = vec![0; self.names.len()];
+        let mut k = 0;
+
+        for _ in 0..self.names.len() {
+            let node = (0..self.names.len())
+                .filter(|&n| !removed[n])
+                .min_by_key(|&n| degrees[n])
+                .expect("Can't be empty");
+
+            k = k.max(degrees[node]);
+            cores[node] = k;
+            removed[node] = true;
+
+            for &next in self.links[node].iter() {
+                if !removed[next] {
+                    degrees[next] -= 1;
+                }
+            }
+        }
+
+        cores
+    }
+
+    /// Graphviz DOT with the given clique highlighted
+    pub fn to_dot(&self, highlight: &[usize]) -> String {
+        let highlight = highlight.iter().collect::<HashSet<_>>();
+        let mut result = String::from("graph lan {\n");
+
+        for (node, name) in self.names.iter().enumerate() {
+            if highlight.contains(&node) {
+                result.push_str(&format!("  {name} [style=filled, fillcolor=red];\n"));
+            }
+        }
+
+        for (a, links) in self.links.iter().enumerate() {
+            for &b in links.iter().filter(|&&b| b > a) {
+                let style = if highlight.contains(&a) && highlight.contains(&b) {
+                    " [color=red, penwidth=2]"
+                } else {
+                    ""
+                };
+                result.push_str(&format!(
+                    "  {} -- {}{style};\n",
+                    self.names[a], self.names[b]
+                ));
+            }
+        }
+
+        result.push_str("}\n");
+        result
+    }
+
+    pub fn password(&self, clique: &[usize]) -> String {
+        let mut names = clique
+            .iter()
+            .map(|&n| self.names[n].as_str())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.join(",")
+    }
+}
+
+fn main() {
+    let data = std::fs::read_to_string("data/day23.txt").expect("Invalid input data");
+    let network = Network::parse(&data);
+
+    if std::env::args().any(|arg| arg == "--stats") {
+        stats(&network);
+        return;
+    }
+
+    let max_clique = network.max_clique();
+
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", network.to_dot(&max_clique));
+        return;
+    }
+
+    println!("A: {}", network.triangles_with_t());
+    println!("B: {}", network.password(&max_clique));
+}
+
+fn stats(network: &Network) {
+    println!("nodes: {}", network.names.len());
+
+    println!("degrees:");
+    for (degree, count) in network.degrees() {
+        println!("  {degree}: {count}");
+    }
+
+    let components = network.components();
+    let sizes = components.iter().map(|c| c.len()).collect::<Vec<_>>();
+    println!("components: {} {:?}", components.len(), sizes);
+
+    let mut cores = BTreeMap::new();
+    for core in network.core_numbers() {
+        *cores.entry(core).or_insert(0) += 1;
+    }
+    println!("k-cores:");
+    for (k, count) in cores {
+        println!("  {k}: {count}");
+    }
+
+    let cliques = network.maximal_cliques();
+    let mut sizes = BTreeMap::new();
+    for clique in cliques.iter() {
+        *sizes.entry(clique.len()).or_insert(0) += 1;
+    }
+    println!("maximal cliques: {}", cliques.len());
+    for (size, count) in sizes {
+        println!("  {size}: {count}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn";
+
+    #[test]
+    fn test_triangles() {
+        let network = Network::parse(EXAMPLE);
+        assert_eq!(network.triangles_with_t(), 7);
+    }
+
+    #[test]
+    fn test_max_clique() {
+        let network = Network::parse(EXAMPLE);
+        let clique = network.max_clique();
+        assert_eq!(network.password(&clique), "co,de,ka,ta");
+    }
+
+    #[test]
+    fn test_degrees() {
+        let network = Network::parse(EXAMPLE);
+        let degrees = network.degrees();
+        let total: usize = degrees.iter().map(|(d, n)| d * n).sum();
+
+        assert_eq!(degrees.values().sum::<usize>(), 16);
+        assert_eq!(total, 32 * 2);
+    }
+
+    #[test]
+    fn test_components() {
+        let network = Network::parse("a-b\nb-c\nd-e");
+        let components = network.components();
+
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].len(), 3);
+        assert_eq!(components[1].len(), 2);
+    }
+
+    #[test]
+    fn test_core_numbers() {
+        // triangle a-b-c with a tail c-d
+        let network = Network::parse("a-b\nb-c\nc-a\nc-d");
+        assert_eq!(network.core_numbers(), [2, 2, 2, 1]);
+    }
+
+    #[test]
+    fn test_maximal_cliques() {
+        let network = Network::parse("a-b\nb-c\nc-a\nc-d");
+        let mut cliques = network
+            .maximal_cliques()
+            .iter()
+            .map(|c| network.password(c))
+            .collect::<Vec<_>>();
+        cliques.sort();
+
+        assert_eq!(cliques, ["a,b,c", "c,d"]);
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let network = Network::parse("a-b\nb-c");
+        let dot = network.to_dot(&[0, 1]);
+
+        assert!(dot.starts_with("graph lan {"));
+        assert!(dot.contains("a [style=filled, fillcolor=red];"));
+        assert!(dot.contains("a -- b [color=red, penwidth=2];"));
+        assert!(dot.contains("b -- c;"));
+    }
+}
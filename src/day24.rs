@@ -0,0 +1,451 @@
+use eyre::{eyre, Result};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    And,
+    Or,
+    Xor,
+}
+
+impl Op {
+    fn parse(input: &str) -> Result<Self> {
+        match input {
+            "AND" => Ok(Self::And),
+            "OR" => Ok(Self::Or),
+            "XOR" => Ok(Self::Xor),
+            _ => Err(eyre!("Invalid gate: {}", input)),
+        }
+    }
+
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Self::And => a && b,
+            Self::Or => a || b,
+            Self::Xor => a ^ b,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Gate {
+    a: String,
+    b: String,
+    op: Op,
+    out: String,
+}
+
+impl Gate {
+    fn parse(input: &str) -> Result<Self> {
+        let parts = input.split_whitespace().collect::<Vec<_>>();
+
+        match parts[..] {
+            [a, op, b, "->", out] => Ok(Self {
+                a: a.to_string(),
+                b: b.to_string(),
+                op: Op::parse(op)?,
+                out: out.to_string(),
+            }),
+            _ => Err(eyre!("Invalid gate: {}", input)),
+        }
+    }
+
+    fn has_input(&self, prefix: char) -> bool {
+        self.a.starts_with(prefix) || self.b.starts_with(prefix)
+    }
+
+    fn is_first_bit(&self) -> bool {
+        self.a.ends_with("00") && self.b.ends_with("00")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Circuit {
+    inits: HashMap<String, bool>,
+    gates: Vec<Gate>,
+}
+
+impl Circuit {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut parts = input.split("\n\n");
+        let inits = parts.next().ok_or_else(|| eyre!("Missing wires"))?;
+        let gates = parts.next().ok_or_else(|| eyre!("Missing gates"))?;
+
+        let inits = inits
+            .lines()
+            .map(|line| {
+                let (wire, value) = line
+                    .split_once(": ")
+                    .ok_or_else(|| eyre!("Invalid wire: {}", line))?;
+                Ok((wire.to_string(), value == "1"))
+            })
+            .collect::<Result<_>>()?;
+
+        let gates = gates.lines().map(Gate::parse).collect::<Result<_>>()?;
+
+        Ok(Self { inits, gates })
+    }
+
+    /// Gate indices in evaluation order
+    fn topological_order(&self) -> Result<Vec<usize>> {
+        let drivers = self
+            .gates
+            .iter()
+            .map(|g| g.out.as_str())
+            .collect::<HashSet<_>>();
+
+        let mut consumers: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut pending = vec![2; self.gates.len()];
+
+        for (idx, gate) in self.gates.iter().enumerate() {
+            for wire in [&gate.a, &gate.b] {
+                if !self.inits.contains_key(wire) && !drivers.contains(wire.as_str()) {
+                    return Err(eyre!("Wire {} is not driven", wire));
+                }
+                consumers.entry(wire).or_default().push(idx);
+            }
+        }
+
+        let mut queue = self
+            .inits
+            .keys()
+            .map(String::as_str)
+            .collect::<VecDeque<_>>();
+        let mut order = vec![];
+
+        while let Some(wire) = queue.pop_front() {
+            for &idx in consumers.get(wire).into_iter().flatten() {
+                pending[idx] -= 1;
+                if pending[idx] == 0 {
+                    order.push(idx);
+                    queue.push_back(&self.gates[idx].out);
+                }
+            }
+        }
+
+        if order.len() < self.gates.len() {
+            let mut cycle = (0..self.gates.len())
+                .filter(|&idx| pending[idx] > 0)
+                .map(|idx| self.gates[idx].out.as_str())
+                .collect::<Vec<_>>();
+            cycle.sort();
+
+            return Err(eyre!("Cycle detected through: {}", cycle.join(",")));
+        }
+
+        Ok(order)
+    }
+
+    pub fn evaluate(&self) -> Result<HashMap<String, bool>> {
+        let mut wires = self.inits.clone();
+
+        for idx in self.topological_order()? {
+            let gate = &self.gates[idx];
+            let value = gate.op.apply(wires[&gate.a], wires[&gate.b]);
+            wires.insert(gate.out.clone(), value);
+        }
+
+        Ok(wires)
+    }
+
+    fn number(wires: &HashMap<String, bool>, prefix: char) -> u64 {
+        wires
+            .iter()
+            .filter(|(wire, &value)| wire.starts_with(prefix) && value)
+            .map(|(wire, _)| wire[1..].parse::<u32>().expect("Invalid wire number"))
+            .fold(0, |acc, bit| acc | (1 << bit))
+    }
+
+    pub fn output(&self) -> Result<u64> {
+        self.evaluate().map(|wires| Self::number(&wires, 'z'))
+    }
+
+    fn input_bits(&self) -> u32 {
+        self.inits.keys().filter(|w| w.starts_with('x')).count() as u32
+    }
+
+    /// Run the circuit with `x` and `y` set to the given numbers
+    pub fn add(&self, x: u64, y: u64) -> Result<u64> {
+        let mut circuit = self.clone();
+        for (wire, value) in circuit.inits.iter_mut() {
+            let bit = wire[1..].parse::<u32>()?;
+            match wire.chars().next() {
+                Some('x') => *value = x >> bit & 1 == 1,
+                Some('y') => *value = y >> bit & 1 == 1,
+                _ => {}
+            }
+        }
+
+        circuit.output()
+    }
+
+    fn is_adder(&self) -> bool {
+        let bits = self.input_bits();
+        let mask = (1 << bits) - 1;
+
+        let mut cases = vec![(0, 0), (mask, 1), (mask, mask)];
+        for bit in 0..bits {
+            cases.push((1 << bit, 0));
+            cases.push((0, 1 << bit));
+            cases.push((1 << bit, 1 << bit));
+        }
+
+        cases
+            .into_iter()
+            .all(|(x, y)| self.add(x, y).map(|z| z == x + y).unwrap_or(false))
+    }
+
+    fn swap(&self, a: &str, b: &str) -> Self {
+        let mut circuit = self.clone();
+        for gate in circuit.gates.iter_mut() {
+            if gate.out == a {
+                gate.out = b.to_string();
+            } else if gate.out == b {
+                gate.out = a.to_string();
+            }
+        }
+
+        circuit
+    }
+
+    /// Output wires breaking the ripple-carry adder structure
+    pub fn suspicious_wires(&self) -> Vec<String> {
+        let last_z = format!("z{:02}", self.input_bits());
+        let feeds = |wire: &str, op: Op| {
+            self.gates
+                .iter()
+                .any(|g| g.op == op && (g.a == wire || g.b == wire))
+        };
+
+        let mut result = self
+            .gates
+            .iter()
+            .filter(|gate| {
+                let is_z = gate.out.starts_with('z');
+                let is_input = gate.has_input('x') || gate.has_input('y');
+
+                match gate.op {
+                    // every z but the final carry is the sum bit
+                    _ if is_z && gate.out == last_z => gate.op != Op::Or,
+                    Op::And | Op::Or if is_z => true,
+                    // carry XOR partial sum only drives z
+                    Op::Xor if !is_input => !is_z,
+                    // x XOR y is the partial sum, used by the next XOR
+                    Op::Xor => !gate.is_first_bit() && !feeds(&gate.out, Op::Xor),
+                    // x AND y and carry AND partial sum are joined by OR
+                    Op::And => !gate.is_first_bit() && !feeds(&gate.out, Op::Or),
+                    Op::Or => false,
+                }
+            })
+            .map(|gate| gate.out.clone())
+            .collect::<Vec<_>>();
+
+        result.sort();
+        result
+    }
+
+    /// Pair up the suspicious wires so the circuit becomes a working adder
+    pub fn swapped_pairs(&self) -> Result<Vec<(String, String)>> {
+        let wires = self.suspicious_wires();
+        if !wires.len().is_multiple_of(2) {
+            return Err(eyre!("Odd number of suspicious wires: {}", wires.join(",")));
+        }
+
+        self.find_pairs(&wires)
+            .ok_or_else(|| eyre!("No swap of {} makes an adder", wires.join(",")))
+    }
+
+    fn find_pairs(&self, wires: &[String]) -> Option<Vec<(String, String)>> {
+        let Some((first, rest)) = wires.split_first() else {
+            return self.is_adder().then(Vec::new);
+        };
+
+        for idx in 0..rest.len() {
+            let mut others = rest.to_vec();
+            let second = others.remove(idx);
+
+            let swapped = self.swap(first, &second);
+            if let Some(mut pairs) = swapped.find_pairs(&others) {
+                pairs.insert(0, (first.clone(), second));
+                return Some(pairs);
+            }
+        }
+
+        None
+    }
+
+    /// Graphviz DOT with a node per gate and an edge per wire
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph circuit {\n");
+        let drivers = self
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(idx, g)| (g.out.as_str(), idx))
+            .collect::<HashMap<_, _>>();
+
+        let mut inits = self.inits.keys().collect::<Vec<_>>();
+        inits.sort();
+        for wire in inits {
+            result.push_str(&format!("  {wire} [shape=box];\n"));
+        }
+
+        for (idx, gate) in self.gates.iter().enumerate() {
+            result.push_str(&format!("  g{idx} [label=\"{}\"];\n", gate.op.name()));
+
+            for wire in [&gate.a, &gate.b] {
+                match drivers.get(wire.as_str()) {
+                    Some(from) => {
+                        result.push_str(&format!("  g{from} -> g{idx} [label=\"{wire}\"];\n"))
+                    }
+                    None => result.push_str(&format!("  {wire} -> g{idx};\n")),
+                }
+            }
+
+            if gate.out.starts_with('z') {
+                result.push_str(&format!("  {} [shape=box];\n", gate.out));
+                result.push_str(&format!("  g{idx} -> {};\n", gate.out));
+            }
+        }
+
+        result.push_str("}\n");
+        result
+    }
+}
+
+fn main() -> Result<()> {
+    let data = std::fs::read_to_string("data/day24.txt")?;
+    let circuit = Circuit::parse(&data)?;
+
+    if std::env::args().any(|arg| arg == "--dot") {
+        print!("{}", circuit.to_dot());
+        return Ok(());
+    }
+
+    println!("A: {}", circuit.output()?);
+
+    let pairs = circuit.swapped_pairs()?;
+    for (a, b) in pairs.iter() {
+        println!("swap {} <-> {}", a, b);
+    }
+
+    let mut wires = pairs
+        .into_iter()
+        .flat_map(|(a, b)| [a, b])
+        .collect::<Vec<_>>();
+    wires.sort();
+    println!("B: {}", wires.join(","));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "x00: 1
+x01: 1
+x02: 1
+y00: 0
+y01: 1
+y02: 0
+
+x00 AND y00 -> z00
+x01 XOR y01 -> z01
+x02 OR y02 -> z02";
+
+    fn ripple_carry(bits: usize) -> String {
+        let mut lines = vec![];
+        for i in 0..bits {
+            lines.push(format!("x{i:02}: 0"));
+        }
+        for i in 0..bits {
+            lines.push(format!("y{i:02}: 0"));
+        }
+        lines.push(String::new());
+
+        lines.push("x00 XOR y00 -> z00".to_string());
+        lines.push("x00 AND y00 -> c00".to_string());
+
+        for i in 1..bits {
+            let carry = if i == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            };
+
+            lines.push(format!("x{i:02} XOR y{i:02} -> s{i:02}"));
+            lines.push(format!("x{i:02} AND y{i:02} -> a{i:02}"));
+            lines.push(format!("s{i:02} XOR c{:02} -> z{i:02}", i - 1));
+            lines.push(format!("s{i:02} AND c{:02} -> b{i:02}", i - 1));
+            lines.push(format!("a{i:02} OR b{i:02} -> {carry}"));
+        }
+
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_output() {
+        let circuit = Circuit::parse(EXAMPLE).unwrap();
+        assert_eq!(circuit.output().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let circuit = Circuit::parse("x00: 1\n\nx00 AND b -> a\nx00 OR a -> b").unwrap();
+        let error = circuit.output().unwrap_err();
+        assert_eq!(error.to_string(), "Cycle detected through: a,b");
+    }
+
+    #[test]
+    fn test_not_driven() {
+        let circuit = Circuit::parse("x00: 1\n\nx00 AND q -> z00").unwrap();
+        assert!(circuit.output().is_err());
+    }
+
+    #[test]
+    fn test_adder() {
+        let circuit = Circuit::parse(&ripple_carry(8)).unwrap();
+
+        assert_eq!(circuit.add(200, 100).unwrap(), 300);
+        assert!(circuit.is_adder());
+        assert!(circuit.suspicious_wires().is_empty());
+    }
+
+    #[test]
+    fn test_swapped_pairs() {
+        let circuit = Circuit::parse(&ripple_carry(8)).unwrap();
+        let broken = circuit.swap("z03", "a03").swap("s05", "b05");
+
+        assert!(!broken.is_adder());
+        assert_eq!(broken.suspicious_wires(), ["a03", "b05", "s05", "z03"]);
+
+        let pairs = broken.swapped_pairs().unwrap();
+        assert_eq!(
+            pairs,
+            [
+                ("a03".to_string(), "z03".to_string()),
+                ("b05".to_string(), "s05".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let circuit = Circuit::parse(EXAMPLE).unwrap();
+        let dot = circuit.to_dot();
+
+        assert!(dot.starts_with("digraph circuit {"));
+        assert!(dot.contains("g0 [label=\"AND\"];"));
+        assert!(dot.contains("x00 -> g0;"));
+        assert!(dot.contains("g0 -> z00;"));
+    }
+}
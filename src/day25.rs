@@ -0,0 +1,278 @@
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Lock,
+    Key,
+}
+
+#[derive(Debug)]
+struct Schematic {
+    kind: Kind,
+    heights: Vec<usize>,
+    /// Every `#` is one bit, packed row by row into as many words as needed
+    mask: Vec<u64>,
+    width: usize,
+    rows: usize,
+}
+
+impl Schematic {
+    /// Locks have the top row filled, keys the bottom one. A lock and a key
+    /// fit when their masks don't intersect
+    pub fn parse(input: &str) -> Result<Self> {
+        let rows = input.lines().collect::<Vec<_>>();
+        let (first, last) = match rows[..] {
+            [first, .., last] => (first, last),
+            _ => return Err(eyre!("Schematic needs at least two rows")),
+        };
+
+        let width = first.len();
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(eyre!("Schematic rows differ in width"));
+        }
+
+        let filled = |row: &str| row.chars().all(|c| c == '#');
+        let kind = if filled(first) {
+            Kind::Lock
+        } else if filled(last) {
+            Kind::Key
+        } else {
+            return Err(eyre!("Schematic is neither a lock nor a key"));
+        };
+
+        let mut heights = vec![0; width];
+        let mut mask = vec![0; (width * rows.len()).div_ceil(64)];
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    let bit = y * width + x;
+                    heights[x] += 1;
+                    mask[bit / 64] |= 1 << (bit % 64);
+                }
+            }
+        }
+
+        // the filled base row is not counted in the height
+        heights.iter_mut().for_each(|h| *h -= 1);
+
+        Ok(Self {
+            kind,
+            heights,
+            mask,
+            width,
+            rows: rows.len(),
+        })
+    }
+}
+
+fn fits(lock: &[u64], key: &[u64]) -> bool {
+    lock.iter().zip(key).all(|(l, k)| l & k == 0)
+}
+
+#[derive(Debug)]
+struct Schematics(Vec<Schematic>);
+
+impl Schematics {
+    pub fn parse(input: &str) -> Result<Self> {
+        let items = input
+            .split("\n\n")
+            .map(Schematic::parse)
+            .collect::<Result<Vec<_>>>()?;
+
+        if items.windows(2).any(|w| w[0].rows != w[1].rows) {
+            return Err(eyre!("Schematics differ in height"));
+        }
+
+        if items.windows(2).any(|w| w[0].width != w[1].width) {
+            return Err(eyre!("Schematics differ in width"));
+        }
+
+        Ok(Self(items))
+    }
+
+    fn masks(&self, kind: Kind) -> HashMap<&[u64], usize> {
+        let mut result = HashMap::new();
+        for item in self.0.iter().filter(|s| s.kind == kind) {
+            *result.entry(&item.mask[..]).or_insert(0) += 1;
+        }
+
+        result
+    }
+
+    /// Count fitting lock/key pairs, identical schematics are compared once
+    pub fn fitting_pairs(&self) -> usize {
+        let locks = self.masks(Kind::Lock);
+        let keys = self.masks(Kind::Key);
+
+        locks
+            .iter()
+            .map(|(lock, lock_count)| {
+                keys.iter()
+                    .filter(|(key, _)| fits(lock, key))
+                    .map(|(_, key_count)| lock_count * key_count)
+                    .sum::<usize>()
+            })
+            .sum()
+    }
+}
+
+fn main() -> Result<()> {
+    let data = std::fs::read_to_string("data/day25.txt")?;
+    let schematics = Schematics::parse(&data)?;
+
+    if std::env::args().any(|arg| arg == "--heights") {
+        for item in schematics.0.iter() {
+            println!("{:?} {:?}", item.kind, item.heights);
+        }
+    }
+
+    println!("A: {}", schematics.fitting_pairs());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
+
+    #[test]
+    fn test_parse() {
+        let schematics = Schematics::parse(EXAMPLE).unwrap();
+        let heights = schematics
+            .0
+            .iter()
+            .map(|s| (s.kind, s.heights.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            heights,
+            [
+                (Kind::Lock, vec![0, 5, 3, 4, 3]),
+                (Kind::Lock, vec![1, 2, 0, 5, 3]),
+                (Kind::Key, vec![5, 0, 2, 1, 3]),
+                (Kind::Key, vec![4, 3, 4, 0, 2]),
+                (Kind::Key, vec![3, 0, 2, 0, 1]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fits() {
+        let schematics = Schematics::parse(EXAMPLE).unwrap();
+        let [lock, _, _, _, key] = &schematics.0[..] else {
+            panic!("Expected 5 schematics");
+        };
+
+        assert!(fits(&lock.mask, &key.mask));
+        assert!(!fits(&lock.mask, &schematics.0[2].mask));
+    }
+
+    #[test]
+    fn test_fitting_pairs() {
+        let schematics = Schematics::parse(EXAMPLE).unwrap();
+        assert_eq!(schematics.fitting_pairs(), 3);
+    }
+
+    #[test]
+    fn test_large_schematics() {
+        // 20 columns and 40 rows, the masks take 13 words
+        let schematic = |kind: Kind, heights: &[usize]| {
+            (0..40)
+                .map(|y| {
+                    heights
+                        .iter()
+                        .map(|&h| match kind {
+                            Kind::Lock if y <= h => '#',
+                            Kind::Key if y >= 39 - h => '#',
+                            _ => '.',
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        // the last key only collides in the last column, deep in the mask
+        let mut tall_last = vec![18; 20];
+        tall_last[19] = 19;
+
+        let input = [
+            schematic(Kind::Lock, &[20; 20]),
+            schematic(Kind::Key, &[18; 20]),
+            schematic(Kind::Key, &[19; 20]),
+            schematic(Kind::Key, &tall_last),
+        ]
+        .join("\n\n");
+        let schematics = Schematics::parse(&input).unwrap();
+        let [lock, fitting, tall, tall_last] = &schematics.0[..] else {
+            panic!("Expected 4 schematics");
+        };
+
+        assert_eq!(lock.heights, vec![20; 20]);
+        assert_eq!(lock.mask.len(), 13);
+        assert!(fits(&lock.mask, &fitting.mask));
+        assert!(!fits(&lock.mask, &tall.mask));
+        assert!(!fits(&lock.mask, &tall_last.mask));
+        assert_eq!(schematics.fitting_pairs(), 1);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input| Schematics::parse(input).unwrap_err().to_string();
+
+        assert_eq!(error("#."), "Schematic needs at least two rows");
+        assert_eq!(error(".#\n#."), "Schematic is neither a lock nor a key");
+        assert_eq!(error("##\n#"), "Schematic rows differ in width");
+        assert_eq!(error("##\n..\n\n..\n##\n##"), "Schematics differ in height");
+        assert_eq!(error("##\n..\n\n...\n###"), "Schematics differ in width");
+    }
+
+    #[test]
+    fn test_fitting_pairs_duplicates() {
+        let doubled = format!("{EXAMPLE}\n\n{EXAMPLE}");
+        let schematics = Schematics::parse(&doubled).unwrap();
+        assert_eq!(schematics.fitting_pairs(), 12);
+    }
+}